@@ -0,0 +1,824 @@
+// Headless companion to the overlay, for linting and converting combo files
+// from scripts without opening a window.
+
+use egui_note::collections::Collections;
+use egui_note::combo::{self, Character, Combo};
+use egui_note::damage;
+use egui_note::duplicates;
+use egui_note::formats::{self, Format};
+use egui_note::frame_data::{self, Severity};
+use egui_note::icon_pack;
+use egui_note::library;
+use egui_note::notation;
+use egui_note::profile::{self, Profile};
+use egui_note::recorder::{self, MatchOptions};
+use egui_note::render;
+use egui_note::schema;
+use egui_note::search;
+use egui_note::settings::{self, Settings};
+use egui_note::wiki;
+use regex::Regex;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::process::ExitCode;
+
+const USAGE: &str = "usage: egui_note-cli <command> [options]
+
+commands:
+  validate <input_game.json>...           check combos, links against frame data and the profile,
+                                          non-zero exit on problems
+  list <file> [character]                 list characters, or one character's combos
+  convert <file> --to <json|md|csv|txt>   convert a game file or an export to another format
+        [--character <name>] [--into <input_game.json>] [--normalize] [-o <out>]
+  render <input_game.json> <character> <combo> [-o <out.png>]
+                                          draw a combo (index or name) to a png
+  damage <input_game.json> [character] [--write]
+                                          estimate damage from frame data, --write stores it
+                                          in each combo's metadata
+  check <input_game.json> <character> <combo> <events.log>
+        [--link-window <frames>] [--cancel-window <frames>]
+                                          check a recorded event log against a combo
+  duplicates <input_game.json> [character] [--distance <tokens>] [--merge]
+                                          list exact and near duplicate combos (default
+                                          distance 2, at most a quarter of the shorter
+                                          route), --merge folds each into the first
+  merge <base.json> <ours.json> <theirs.json> [-o <out.json>]
+                                          three-way merge of two edited copies of a library
+                                          by combo id, written over ours unless -o
+  migrate <input_game.json>... [--dry-run]
+                                          bring game files up to the current schema
+                                          version, --dry-run only reports what would change
+  compact <input_game.json> [character] [--collections <collections.json>] [--dry-run]
+                                          drop null and deleted combos and renumber the
+                                          rest, --collections updates what points at them
+  wiki <input_game.json> <page.html|.txt> [character] [--add]
+                                          preview the combos in a saved wiki page or text,
+                                          in the game's notation, --add adds them to the
+                                          character
+  search [query]... [--data-dir <dir>] [--saved <name>] [--save <name>]
+                                          search every game's combos with the overlay's
+                                          query language (default dir: src/games), --saved
+                                          runs a saved query, --save stores this one
+
+options:
+  --images <dir>                          where icon packs live (default: images)";
+
+struct Args {
+    positional: Vec<String>,
+    options: HashMap<String, String>,
+    flags: Vec<String>,
+}
+
+impl Args {
+    // `takes_value` lists the options that are followed by a value, `flags` the
+    // ones that aren't. Anything else starting with "--" is a mistake, a lone
+    // "-word" is positional (search negates with it).
+    fn parse(args: &[String], takes_value: &[&str], flags: &[&str]) -> Result<Self, String> {
+        let mut parsed = Args {
+            positional: Vec::new(),
+            options: HashMap::new(),
+            flags: Vec::new(),
+        };
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            if takes_value.contains(&arg.as_str()) {
+                let value = args
+                    .next()
+                    .ok_or_else(|| format!("{} needs a value", arg))?;
+                parsed.options.insert(arg.to_owned(), value.to_owned());
+            } else if flags.contains(&arg.as_str()) {
+                parsed.flags.push(arg.to_owned());
+            } else if arg.starts_with("--") {
+                return Err(format!("unknown option '{}'\n\n{}", arg, USAGE));
+            } else {
+                parsed.positional.push(arg.to_owned());
+            }
+        }
+        Ok(parsed)
+    }
+
+    fn option(&self, name: &str) -> Option<&str> {
+        self.options.get(name).map(|v| v.as_str())
+    }
+
+    fn flag(&self, name: &str) -> bool {
+        self.flags.iter().any(|f| f == name)
+    }
+
+    fn images(&self) -> &str {
+        self.option("--images").unwrap_or(icon_pack::IMAGES)
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let rest = args.get(1..).unwrap_or_default();
+    let result = match args.first().map(|a| a.as_str()) {
+        Some("validate") => validate(rest),
+        Some("list") => list(rest),
+        Some("convert") => convert(rest),
+        Some("render") => render_combo(rest),
+        Some("check") => check(rest),
+        Some("damage") => damage_estimates(rest),
+        Some("search") => search_combos(rest),
+        Some("duplicates") => find_duplicates(rest),
+        Some("merge") => merge_libraries(rest),
+        Some("migrate") => migrate(rest),
+        Some("compact") => compact(rest),
+        Some("wiki") => import_wiki(rest),
+        Some("help") | Some("--help") | Some("-h") | None => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        Some(other) => Err(format!("unknown command '{}'\n\n{}", other, USAGE)),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn read_json(path: &str) -> Result<Value, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("unable to read {}: {}", path, e))?;
+    serde_json::from_str(&text).map_err(|e| format!("bad json in {}: {}", path, e))
+}
+
+// a game file brought up to the current schema, in memory only
+fn read_game(path: &str) -> Result<Value, String> {
+    let mut game = read_json(path)?;
+    schema::migrate(&mut game).map_err(|e| format!("{}: {}", path, e))?;
+    Ok(game)
+}
+
+// characters from a game file or from a .md/.csv/.txt export
+fn read_characters(path: &str) -> Result<Vec<Character>, String> {
+    let ext = Path::new(path)
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_string())
+        .unwrap_or_default();
+    if ext.eq_ignore_ascii_case("json") {
+        return combo::characters_from_json(&read_game(path)?)
+            .map_err(|e| format!("{}: {}", path, e));
+    }
+    let format =
+        Format::from_extension(&ext).ok_or_else(|| format!("{}: unknown file type", path))?;
+    let text = fs::read_to_string(path).map_err(|e| format!("unable to read {}: {}", path, e))?;
+    formats::import(&text, format).map_err(|e| format!("{}: {}", path, e))
+}
+
+fn find_character<'a>(characters: &'a [Character], name: &str) -> Result<&'a Character, String> {
+    characters
+        .iter()
+        .find(|c| c.name.eq_ignore_ascii_case(name))
+        .ok_or_else(|| format!("no character '{}'", name))
+}
+
+// by index, or by name when it isn't a number
+fn find_combo<'a>(character: &'a Character, combo: &str) -> Result<&'a Combo, String> {
+    character
+        .find_combo(combo)
+        .and_then(|index| character.live_combo(index))
+        .ok_or_else(|| format!("{} has no combo '{}'", character.name, combo))
+}
+
+fn validate(args: &[String]) -> Result<(), String> {
+    let args = Args::parse(args, &["--images"], &[])?;
+    if args.positional.is_empty() {
+        return Err(USAGE.to_owned());
+    }
+    let default_json = profile::default_json();
+    let packs = icon_pack::discover(Path::new(args.images()));
+    let mut failed = false;
+    for path in args.positional.iter() {
+        let mut problems = Vec::new();
+        match read_game(path) {
+            Ok(game) => {
+                let profile = Profile {
+                    game: &game,
+                    default: &default_json,
+                    packs: &packs,
+                };
+                problems.extend(profile.problems());
+                match combo::characters_from_json(&game) {
+                    Ok(characters) => match notation::token_regex(&profile.known_tokens()) {
+                        Ok(re) => {
+                            problems.extend(combo_problems(&profile, &re, &characters));
+                            problems.extend(link_problems(&profile, &re, &characters));
+                        }
+                        // the profile's token patterns, every combo would fail the same way
+                        Err(e) => problems.push(format!("unable to map inputs: {}", e)),
+                    },
+                    Err(e) => problems.push(e),
+                }
+            }
+            Err(e) => problems.push(e),
+        }
+        if problems.is_empty() {
+            println!("{}: ok", path);
+        } else {
+            failed = true;
+            for problem in problems {
+                println!("{}: {}", path, problem);
+            }
+        }
+    }
+    if failed {
+        Err("validation failed".to_owned())
+    } else {
+        Ok(())
+    }
+}
+
+// the combo's inputs as token lines, like Profile::map_inputs with the
+// profile's regex built once up front
+fn map_inputs(re: &Regex, combo: &Combo) -> Vec<Vec<String>> {
+    combo
+        .inputs
+        .replace(',', " ")
+        .split('\n')
+        .map(|line| notation::tokenize(line, re))
+        .collect()
+}
+
+// tokens nothing in the profile knows about (they show as the error icon)
+fn combo_problems(profile: &Profile, re: &Regex, characters: &[Character]) -> Vec<String> {
+    let mut problems = Vec::new();
+    for character in characters {
+        for (index, combo) in character.combos.iter().enumerate() {
+            let combo = match combo {
+                Some(combo) if combo.deleted => continue,
+                Some(combo) => combo,
+                None => {
+                    problems.push(format!(
+                        "{} #{}: combo is null, `compact` removes it",
+                        character.name, index
+                    ));
+                    continue;
+                }
+            };
+            let lines = map_inputs(re, combo);
+            for (line, tokens) in lines.iter().enumerate() {
+                let unknown: Vec<&str> = tokens
+                    .iter()
+                    .filter(|t| profile.icon_path(t).is_none())
+                    .map(|t| t.as_str())
+                    .collect();
+                if !unknown.is_empty() {
+                    problems.push(format!(
+                        "{} #{} '{}' line {}: unknown {:?}",
+                        character.name,
+                        index,
+                        combo.name,
+                        line + 1,
+                        unknown
+                    ));
+                }
+            }
+        }
+    }
+    problems
+}
+
+// links and cancels the character's frame data says can't work
+fn link_problems(profile: &Profile, re: &Regex, characters: &[Character]) -> Vec<String> {
+    let mut problems = Vec::new();
+    let buttons = profile.buttons();
+    for character in characters {
+        let data = match frame_data::character_frame_data(profile.game, &character.name) {
+            Ok(data) if data.is_empty() => continue,
+            Ok(data) => data,
+            Err(e) => {
+                problems.push(e);
+                continue;
+            }
+        };
+        for (index, combo) in character.live() {
+            let lines = map_inputs(re, combo);
+            let moves = recorder::moves(&lines, &buttons);
+            for finding in frame_data::analyse(&moves, &data) {
+                if finding.severity == Severity::Impossible {
+                    problems.push(format!(
+                        "{} #{} '{}': {}",
+                        character.name, index, combo.name, finding.message
+                    ));
+                }
+            }
+        }
+    }
+    problems
+}
+
+fn list(args: &[String]) -> Result<(), String> {
+    let args = Args::parse(args, &[], &[])?;
+    let path = args.positional.first().ok_or(USAGE)?;
+    let characters = read_characters(path)?;
+    match args.positional.get(1) {
+        None => {
+            for character in characters.iter() {
+                let count = character.live().count();
+                println!("{} ({} combos)", character.name, count);
+            }
+        }
+        Some(name) => {
+            let character = find_character(&characters, name)?;
+            for (index, combo) in character.combos.iter().enumerate() {
+                match combo {
+                    Some(combo) if combo.deleted => {
+                        println!("{:>3}  (deleted) {}", index, combo.name)
+                    }
+                    Some(combo) => println!(
+                        "{:>3}  {:<8} {}  {}",
+                        index,
+                        combo.state.name(),
+                        combo.name,
+                        combo.inputs.replace('\n', " / ")
+                    ),
+                    None => println!("{:>3}  (null)", index),
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn convert(args: &[String]) -> Result<(), String> {
+    let args = Args::parse(
+        args,
+        &["--to", "-o", "--character", "--into"],
+        &["--normalize"],
+    )?;
+    let path = args.positional.first().ok_or(USAGE)?;
+    let to = args.option("--to").ok_or("convert needs --to")?;
+    let mut characters = read_characters(path)?;
+    if let Some(name) = args.option("--character") {
+        // exports without a character name belong to the one asked for
+        for character in characters.iter_mut().filter(|c| c.name.is_empty()) {
+            character.name = name.to_owned();
+        }
+        characters.retain(|c| c.name.eq_ignore_ascii_case(name));
+    }
+    if args.flag("--normalize") {
+        for combo in characters
+            .iter_mut()
+            .flat_map(|c| c.combos.iter_mut().flatten())
+        {
+            combo.inputs = notation::normalize(&combo.inputs);
+        }
+    }
+    let output = if to.eq_ignore_ascii_case("json") {
+        let mut game = match args.option("--into") {
+            Some(into) => read_game(into)?,
+            None => serde_json::json!({ schema::KEY: schema::CURRENT, "characters": {} }),
+        };
+        for character in characters.iter() {
+            if character.name.is_empty() {
+                return Err("combos without a character, pass --character".to_owned());
+            }
+            combo::set_character(&mut game, character);
+        }
+        serde_json::to_string_pretty(&game).unwrap()
+    } else {
+        let format =
+            Format::from_extension(to).ok_or_else(|| format!("unknown format '{}'", to))?;
+        formats::export(&characters, format)
+    };
+    match args.option("-o") {
+        Some(out) => fs::write(out, output).map_err(|e| format!("unable to write {}: {}", out, e)),
+        None => {
+            print!("{}", output);
+            Ok(())
+        }
+    }
+}
+
+fn render_combo(args: &[String]) -> Result<(), String> {
+    let args = Args::parse(args, &["-o", "--images"], &[])?;
+    let (path, character, combo) = match args.positional.as_slice() {
+        [path, character, combo] => (path, character, combo),
+        _ => return Err(USAGE.to_owned()),
+    };
+    let game = read_game(path)?;
+    let characters = combo::characters_from_json(&game)?;
+    let character = find_character(&characters, character)?;
+    let combo = find_combo(character, combo)?;
+    let default_json = profile::default_json();
+    let packs = icon_pack::discover(Path::new(args.images()));
+    let profile = Profile {
+        game: &game,
+        default: &default_json,
+        packs: &packs,
+    };
+    let game_name = Path::new(path)
+        .file_stem()
+        .map(|s| s.to_string_lossy().trim_start_matches("input_").to_owned())
+        .unwrap_or_default();
+    let strip = render::ComboStrip {
+        title: combo.name.clone(),
+        metadata: vec![
+            format!("Game: {}", game_name),
+            format!("Character: {}", character.name),
+            format!("State: {:?}", combo.state),
+        ],
+        lines: profile
+            .map_inputs(&combo.inputs.replace(',', " "))
+            .map_err(|e| e.to_string())?,
+    };
+    let out = match args.option("-o") {
+        Some(out) => out.to_owned(),
+        None => format!("{}.png", render::file_stem(&combo.name)),
+    };
+    let icon = |token: &str| profile.icon_path(token).or_else(|| profile.icon_path("_"));
+    render::export_png(
+        Path::new(&out),
+        &strip,
+        icon,
+        &render::ExportOptions::default(),
+    )?;
+    println!("saved {}", out);
+    Ok(())
+}
+
+fn check(args: &[String]) -> Result<(), String> {
+    let args = Args::parse(args, &["--images", "--link-window", "--cancel-window"], &[])?;
+    let (path, character, combo, log) = match args.positional.as_slice() {
+        [path, character, combo, log] => (path, character, combo, log),
+        _ => return Err(USAGE.to_owned()),
+    };
+    let game = read_game(path)?;
+    let characters = combo::characters_from_json(&game)?;
+    let character = find_character(&characters, character)?;
+    let combo = find_combo(character, combo)?;
+    let default_json = profile::default_json();
+    let packs = icon_pack::discover(Path::new(args.images()));
+    let profile = Profile {
+        game: &game,
+        default: &default_json,
+        packs: &packs,
+    };
+    let text = fs::read_to_string(log).map_err(|e| format!("unable to read {}: {}", log, e))?;
+    let events = recorder::parse_log(&text).map_err(|e| format!("{}: {}", log, e))?;
+    let lines = profile
+        .map_inputs(&combo.inputs.replace(',', " "))
+        .map_err(|e| e.to_string())?;
+    let moves = recorder::moves(&lines, &profile.buttons());
+    let mut options = MatchOptions::default();
+    for (name, window) in [
+        ("--link-window", &mut options.link_window),
+        ("--cancel-window", &mut options.cancel_window),
+    ] {
+        if let Some(value) = args.option(name) {
+            *window = value
+                .parse()
+                .map_err(|_| format!("{} needs a number of frames", name))?;
+        }
+    }
+    let report = recorder::check(&moves, &events, &options);
+    println!("{}/{} moves", report.matched, report.total);
+    match report.miss {
+        Some(miss) => Err(miss.to_string()),
+        None => Ok(()),
+    }
+}
+
+fn damage_estimates(args: &[String]) -> Result<(), String> {
+    let args = Args::parse(args, &["--images"], &["--write"])?;
+    let path = args.positional.first().ok_or(USAGE)?;
+    let mut game = read_game(path)?;
+    let mut characters = combo::characters_from_json(&game)?;
+    if let Some(name) = args.positional.get(1) {
+        find_character(&characters, name)?;
+        characters.retain(|c| c.name.eq_ignore_ascii_case(name));
+    }
+    let default_json = profile::default_json();
+    let packs = icon_pack::discover(Path::new(args.images()));
+    let mut changed = Vec::new();
+    {
+        let profile = Profile {
+            game: &game,
+            default: &default_json,
+            packs: &packs,
+        };
+        for character in characters.iter_mut() {
+            let mut character_changed = false;
+            for (index, combo) in character.combos.iter_mut().enumerate() {
+                let combo = match combo {
+                    Some(combo) if !combo.deleted => combo,
+                    _ => continue,
+                };
+                let estimate = match damage::for_inputs(&profile, &character.name, &combo.inputs)? {
+                    Some(estimate) => estimate,
+                    None => continue,
+                };
+                println!(
+                    "{} #{} '{}': {} damage, {} hits, meter {:+}{}",
+                    character.name,
+                    index,
+                    combo.name,
+                    estimate.damage,
+                    estimate.hits,
+                    estimate.meter,
+                    if estimate.missing.is_empty() {
+                        "".to_owned()
+                    } else {
+                        format!(" (no data for {})", estimate.missing.join(", "))
+                    }
+                );
+                character_changed |= damage::store(combo, &estimate);
+            }
+            if character_changed {
+                changed.push(character.clone());
+            }
+        }
+    }
+    if args.flag("--write") && !changed.is_empty() {
+        for character in changed.iter() {
+            combo::set_character(&mut game, character);
+        }
+        fs::write(path, serde_json::to_string_pretty(&game).unwrap())
+            .map_err(|e| format!("unable to write {}: {}", path, e))?;
+        println!("updated {} character(s) in {}", changed.len(), path);
+    }
+    Ok(())
+}
+
+fn search_combos(args: &[String]) -> Result<(), String> {
+    let args = Args::parse(args, &["--images", "--data-dir", "--saved", "--save"], &[])?;
+    let data_dir = Path::new(args.option("--data-dir").unwrap_or("src/games"));
+    let settings_path = data_dir.join(settings::SETTINGS);
+    let mut settings = Settings::load(&settings_path);
+    let query = match args.option("--saved") {
+        Some(name) => settings
+            .saved_queries
+            .get(name)
+            .cloned()
+            .ok_or_else(|| format!("no saved query '{}'", name))?,
+        None if args.positional.is_empty() => return Err(USAGE.to_owned()),
+        None => args.positional.join(" "),
+    };
+    let game_list = read_json(&data_dir.join("game_list.json").to_string_lossy())?;
+    let default_json = profile::default_json();
+    let packs = icon_pack::discover(Path::new(args.images()));
+    let (index, errors) = search::Index::build(data_dir, &game_list, &default_json, &packs);
+    for error in errors {
+        eprintln!("{}", error);
+    }
+    let hits = index.search(&query)?;
+    for hit in hits.iter() {
+        println!(
+            "{} / {} #{} '{}': {}",
+            hit.game,
+            hit.character,
+            hit.index,
+            hit.combo.name,
+            hit.combo.inputs.replace('\n', " ")
+        );
+    }
+    println!("{} combo(s)", hits.len());
+    if let Some(name) = args.option("--save") {
+        settings.saved_queries.insert(name.to_owned(), query);
+        settings.save(&settings_path)?;
+        println!("saved query '{}'", name);
+    }
+    Ok(())
+}
+
+fn find_duplicates(args: &[String]) -> Result<(), String> {
+    let args = Args::parse(args, &["--images", "--distance"], &["--merge"])?;
+    let path = args.positional.first().ok_or(USAGE)?;
+    let mut game = read_game(path)?;
+    let mut characters = combo::characters_from_json(&game)?;
+    if let Some(name) = args.positional.get(1) {
+        find_character(&characters, name)?;
+        characters.retain(|c| c.name.eq_ignore_ascii_case(name));
+    }
+    let max_distance = match args.option("--distance") {
+        Some(value) => value
+            .parse()
+            .map_err(|_| "--distance needs a number of tokens".to_owned())?,
+        None => 2,
+    };
+    let default_json = profile::default_json();
+    let packs = icon_pack::discover(Path::new(args.images()));
+    let mut merged = 0;
+    for character in characters.iter_mut() {
+        let found = {
+            let profile = Profile {
+                game: &game,
+                default: &default_json,
+                packs: &packs,
+            };
+            duplicates::find(&profile, character, max_distance)?
+        };
+        for duplicate in found.iter() {
+            let name = |index: usize| {
+                character.combos[index]
+                    .as_ref()
+                    .map(|c| c.name.clone())
+                    .unwrap_or_default()
+            };
+            println!(
+                "{} #{} '{}' and #{} '{}': {}",
+                character.name,
+                duplicate.first,
+                name(duplicate.first),
+                duplicate.second,
+                name(duplicate.second),
+                match duplicate.distance {
+                    0 => "exact".to_owned(),
+                    n => format!("{} token(s) apart", n),
+                }
+            );
+        }
+        if args.flag("--merge") && !found.is_empty() {
+            for duplicate in found.iter() {
+                if duplicates::merge_into(character, duplicate) {
+                    merged += 1;
+                }
+            }
+            combo::set_character(&mut game, character);
+        }
+    }
+    if merged > 0 {
+        fs::write(path, serde_json::to_string_pretty(&game).unwrap())
+            .map_err(|e| format!("unable to write {}: {}", path, e))?;
+        println!("merged {} combo(s) in {}", merged, path);
+    }
+    Ok(())
+}
+
+fn merge_libraries(args: &[String]) -> Result<(), String> {
+    let args = Args::parse(args, &["-o"], &[])?;
+    let (base, ours, theirs) = match args.positional.as_slice() {
+        [base, ours, theirs] => (base, ours, theirs),
+        _ => return Err(USAGE.to_owned()),
+    };
+    let mut game = read_game(ours)?;
+    let merged = library::merge3(
+        &read_characters(base)?,
+        &combo::characters_from_json(&game)?,
+        &read_characters(theirs)?,
+    );
+    for character in merged.characters.iter() {
+        combo::set_character(&mut game, character);
+    }
+    for conflict in merged.conflicts.iter() {
+        println!(
+            "conflict: {} '{}': {}",
+            conflict.character, conflict.combo, conflict.message
+        );
+    }
+    let out = args.option("-o").unwrap_or(ours);
+    fs::write(out, serde_json::to_string_pretty(&game).unwrap())
+        .map_err(|e| format!("unable to write {}: {}", out, e))?;
+    println!(
+        "merged into {} with {} conflict(s)",
+        out,
+        merged.conflicts.len()
+    );
+    Ok(())
+}
+
+fn migrate(args: &[String]) -> Result<(), String> {
+    let args = Args::parse(args, &[], &["--dry-run"])?;
+    if args.positional.is_empty() {
+        return Err(USAGE.to_owned());
+    }
+    let dry_run = args.flag("--dry-run");
+    for path in args.positional.iter() {
+        let mut game = read_json(path)?;
+        let report = schema::migrate(&mut game).map_err(|e| format!("{}: {}", path, e))?;
+        if !report.changed() {
+            println!("{}: up to date (v{})", path, report.to);
+            continue;
+        }
+        println!("{}: v{} -> v{}", path, report.from, report.to);
+        for change in report.changes.iter() {
+            println!("  {}", change);
+        }
+        if !dry_run {
+            fs::write(path, serde_json::to_string_pretty(&game).unwrap())
+                .map_err(|e| format!("unable to write {}: {}", path, e))?;
+        }
+    }
+    if dry_run {
+        println!("dry run, nothing written");
+    }
+    Ok(())
+}
+
+fn compact(args: &[String]) -> Result<(), String> {
+    let args = Args::parse(args, &["--collections"], &["--dry-run"])?;
+    let path = args.positional.first().ok_or(USAGE)?;
+    let mut game = read_game(path)?;
+    let mut characters = combo::characters_from_json(&game)?;
+    if let Some(name) = args.positional.get(1) {
+        find_character(&characters, name)?;
+        characters.retain(|c| c.name.eq_ignore_ascii_case(name));
+    }
+    let game_id = Path::new(path)
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let game_id = game_id.strip_prefix("input_").unwrap_or(&game_id);
+    let collections_path = args.option("--collections").map(Path::new);
+    let mut collections = match collections_path {
+        Some(collections_path) => Some(Collections::load(collections_path)?),
+        None => None,
+    };
+    let refs = |c: &Collections| {
+        c.favourites.len() + c.collections.iter().map(|c| c.combos.len()).sum::<usize>()
+    };
+    let mut removed = 0;
+    for character in characters.iter_mut() {
+        // refs from before keys pick theirs up while the indices still fit
+        let refs_before = collections.as_mut().map(|collections| {
+            collections.resolve(game_id, character);
+            refs(collections)
+        });
+        let before = character.combos.len();
+        let moved = character.compact();
+        if moved.len() == before {
+            continue;
+        }
+        removed += before - moved.len();
+        println!(
+            "{}: {} combo(s) removed, {} renumbered",
+            character.name,
+            before - moved.len(),
+            moved.iter().filter(|(old, new)| old != new).count()
+        );
+        combo::set_character(&mut game, character);
+        if let (Some(collections), Some(refs_before)) = (collections.as_mut(), refs_before) {
+            collections.resolve(game_id, character);
+            let dropped = refs_before - refs(collections);
+            if dropped > 0 {
+                println!(
+                    "  {} collection entr(ies) pointed at removed combos",
+                    dropped
+                );
+            }
+        }
+    }
+    if removed == 0 {
+        println!("{}: nothing to compact", path);
+    } else if args.flag("--dry-run") {
+        println!("dry run, nothing written");
+    } else {
+        fs::write(path, serde_json::to_string_pretty(&game).unwrap())
+            .map_err(|e| format!("unable to write {}: {}", path, e))?;
+        if let (Some(collections), Some(collections_path)) = (collections, collections_path) {
+            collections.save(collections_path)?;
+        }
+        println!("compacted {}", path);
+    }
+    Ok(())
+}
+
+fn import_wiki(args: &[String]) -> Result<(), String> {
+    let args = Args::parse(args, &["--images"], &["--add"])?;
+    let (path, page) = match args.positional.as_slice() {
+        [path, page, ..] => (path, page),
+        _ => return Err(USAGE.to_owned()),
+    };
+    let mut game = read_game(path)?;
+    let text = fs::read_to_string(page).map_err(|e| format!("unable to read {}: {}", page, e))?;
+    let default_json = profile::default_json();
+    let packs = icon_pack::discover(Path::new(args.images()));
+    let found = {
+        let profile = Profile {
+            game: &game,
+            default: &default_json,
+            packs: &packs,
+        };
+        wiki::parse(&profile, &text).map_err(|e| format!("{}: {}", page, e))?
+    };
+    for combo in found.iter() {
+        println!("{}: {}", combo.name, combo.inputs);
+        println!("    from {}", combo.source);
+        if !combo.unknown.is_empty() {
+            println!("    unknown {}", combo.unknown.join(", "));
+        }
+    }
+    if !args.flag("--add") {
+        return Ok(());
+    }
+    let name = args
+        .positional
+        .get(2)
+        .ok_or("--add needs a character to add to")?;
+    let characters = combo::characters_from_json(&game)?;
+    let mut character = find_character(&characters, name)?.clone();
+    character
+        .combos
+        .extend(found.iter().map(|combo| Some(combo.to_combo())));
+    combo::set_character(&mut game, &character);
+    fs::write(path, serde_json::to_string_pretty(&game).unwrap())
+        .map_err(|e| format!("unable to write {}: {}", path, e))?;
+    println!("added {} combo(s) to {}", found.len(), character.name);
+    Ok(())
+}
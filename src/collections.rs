@@ -0,0 +1,300 @@
+// Favourites and named collections of combos from any game or character,
+// kept in collections.json in the data directory, apart from the game files.
+//
+// { "favourites": [ref, ...], "collections": [{ "name": "...", "combos": [ref, ...] }] }
+//
+// A ref is { "game": "<id>", "character": "<name>", "key": "<Combo::key>",
+// "index": n, "name": "<combo name>" }. The key is what finds the combo, the
+// index and name are where it was last seen and what it was called, kept up
+// to date by `resolve` whenever the game loads. Refs from before keys go by
+// the index and pick up a key the first time they resolve.
+
+use crate::combo::Character;
+use crate::json_file;
+use serde::Deserialize;
+use serde::Serialize;
+use std::path::Path;
+
+pub const COLLECTIONS: &str = "collections.json";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ComboRef {
+    pub game: String,
+    pub character: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub key: String,
+    pub index: usize,
+    pub name: String,
+}
+
+impl ComboRef {
+    pub fn label(&self) -> String {
+        format!("{} / {} / {}", self.game, self.character, self.name)
+    }
+
+    // same combo, whatever name it was saved with
+    pub fn same(&self, other: &ComboRef) -> bool {
+        let combo = if self.key.is_empty() || other.key.is_empty() {
+            self.index == other.index
+        } else {
+            self.key == other.key
+        };
+        self.game == other.game && self.character == other.character && combo
+    }
+
+    // where the combo is in `character` now, deleted ones included so a
+    // restore brings them back
+    pub fn find(&self, character: &Character) -> Option<usize> {
+        if self.key.is_empty() {
+            return character
+                .combos
+                .get(self.index)?
+                .as_ref()
+                .map(|_| self.index);
+        }
+        character
+            .combos
+            .iter()
+            .enumerate()
+            .find_map(|(i, c)| c.as_ref().filter(|c| c.key(i) == self.key).map(|_| i))
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Collection {
+    pub name: String,
+    pub combos: Vec<ComboRef>,
+}
+
+impl Collection {
+    // false when the combo is already in it
+    pub fn add(&mut self, combo: ComboRef) -> bool {
+        if self.combos.iter().any(|c| c.same(&combo)) {
+            return false;
+        }
+        self.combos.push(combo);
+        true
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct Collections {
+    pub favourites: Vec<ComboRef>,
+    pub collections: Vec<Collection>,
+}
+
+impl Collections {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        json_file::load(path)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        json_file::save(path, self)
+    }
+
+    pub fn is_favourite(&self, combo: &ComboRef) -> bool {
+        self.favourites.iter().any(|c| c.same(combo))
+    }
+
+    pub fn toggle_favourite(&mut self, combo: ComboRef) {
+        if self.is_favourite(&combo) {
+            self.favourites.retain(|c| !c.same(&combo));
+        } else {
+            self.favourites.push(combo);
+        }
+    }
+
+    // Points the refs to `character`'s combos at where they are now, after a
+    // load, merge or compact moved them. Refs to combos that went away are
+    // dropped. True when anything changed.
+    pub fn resolve(&mut self, game: &str, character: &Character) -> bool {
+        let mut changed = false;
+        let lists = std::iter::once(&mut self.favourites)
+            .chain(self.collections.iter_mut().map(|c| &mut c.combos));
+        for list in lists {
+            list.retain_mut(|combo| {
+                if combo.game != game || combo.character != character.name {
+                    return true;
+                }
+                let index = match combo.find(character) {
+                    Some(index) => index,
+                    None => {
+                        changed = true;
+                        return false;
+                    }
+                };
+                let found = character.combos[index].as_ref().unwrap();
+                let key = found.key(index);
+                if combo.index != index || combo.key != key || combo.name != found.name {
+                    combo.index = index;
+                    combo.key = key;
+                    combo.name = found.name.clone();
+                    changed = true;
+                }
+                true
+            });
+        }
+        changed
+    }
+
+    pub fn find(&self, name: &str) -> Option<&Collection> {
+        self.collections
+            .iter()
+            .find(|c| c.name.eq_ignore_ascii_case(name))
+    }
+
+    // the named collection, made empty if there isn't one
+    pub fn get_or_create(&mut self, name: &str) -> &mut Collection {
+        match self
+            .collections
+            .iter()
+            .position(|c| c.name.eq_ignore_ascii_case(name))
+        {
+            Some(i) => &mut self.collections[i],
+            None => {
+                self.collections.push(Collection {
+                    name: name.to_owned(),
+                    combos: Vec::new(),
+                });
+                self.collections.last_mut().unwrap()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::combo::{Combo, ComboState};
+
+    fn combo(id: &str) -> Option<Combo> {
+        let mut combo = Combo::new(id.to_uppercase(), "5LP".to_owned(), ComboState::NotDone);
+        combo.id = id.to_owned();
+        Some(combo)
+    }
+
+    fn ryu(combos: Vec<Option<Combo>>) -> Character {
+        Character::new("Ryu".to_owned(), combos)
+    }
+
+    fn at(character: &Character, index: usize) -> ComboRef {
+        let combo = character.combos[index].as_ref().unwrap();
+        ComboRef {
+            game: "sf".to_owned(),
+            character: character.name.clone(),
+            key: combo.key(index),
+            index,
+            name: combo.name.clone(),
+        }
+    }
+
+    fn keys(list: &[ComboRef]) -> Vec<(&str, usize)> {
+        list.iter().map(|c| (c.key.as_str(), c.index)).collect()
+    }
+
+    #[test]
+    fn same_goes_by_key() {
+        let character = ryu(vec![combo("a"), combo("b")]);
+        let a = at(&character, 0);
+        let mut renamed = a.clone();
+        renamed.name = "other".to_owned();
+        assert!(a.same(&renamed));
+        assert!(!a.same(&at(&character, 1)));
+        // an old ref without a key only has the index
+        let mut old = at(&character, 0);
+        old.key.clear();
+        assert!(old.same(&a));
+        let mut collection = Collection::default();
+        assert!(collection.add(a));
+        assert!(!collection.add(renamed));
+    }
+
+    #[test]
+    fn resolve_follows_reordering() {
+        let before = ryu(vec![combo("a"), combo("b"), combo("c")]);
+        let mut collections = Collections {
+            favourites: vec![at(&before, 2), at(&before, 0)],
+            ..Collections::default()
+        };
+        collections.get_or_create("bnbs").combos = vec![at(&before, 1)];
+        // the file was edited outside the app, "a" is gone and the rest moved
+        let mut after = ryu(vec![combo("c"), None, combo("b")]);
+        after.combos[0].as_mut().unwrap().name = "C renamed".to_owned();
+        assert!(collections.resolve("sf", &after));
+        assert_eq!(keys(&collections.favourites), vec![("c", 0)]);
+        assert_eq!(collections.favourites[0].name, "C renamed");
+        assert_eq!(
+            keys(&collections.find("bnbs").unwrap().combos),
+            vec![("b", 2)]
+        );
+        assert!(!collections.resolve("sf", &after));
+    }
+
+    #[test]
+    fn resolve_fills_in_old_refs() {
+        let character = ryu(vec![combo("a"), combo("b")]);
+        let mut old = at(&character, 1);
+        old.key.clear();
+        let mut collections = Collections {
+            favourites: vec![old],
+            ..Collections::default()
+        };
+        assert!(collections.resolve("sf", &character));
+        assert_eq!(keys(&collections.favourites), vec![("b", 1)]);
+    }
+
+    #[test]
+    fn resolve_leaves_others_alone() {
+        let character = ryu(vec![combo("a")]);
+        let mut elsewhere = at(&character, 0);
+        elsewhere.game = "skg".to_owned();
+        let mut ken = at(&character, 0);
+        ken.character = "Ken".to_owned();
+        let mut collections = Collections {
+            favourites: vec![elsewhere.clone(), ken.clone()],
+            ..Collections::default()
+        };
+        assert!(!collections.resolve("sf", &ryu(Vec::new())));
+        assert_eq!(collections.favourites, vec![elsewhere, ken]);
+    }
+
+    #[test]
+    fn deleted_combos_stay_until_compacted() {
+        let mut character = ryu(vec![combo("a"), combo("b")]);
+        let mut collections = Collections {
+            favourites: vec![at(&character, 1)],
+            ..Collections::default()
+        };
+        character.combos[1].as_mut().unwrap().deleted = true;
+        assert!(!collections.resolve("sf", &character));
+        assert_eq!(collections.favourites.len(), 1);
+    }
+
+    #[test]
+    fn refs_follow_a_compact() {
+        // a 0, null 1, b deleted 2, c 3, d 4
+        let mut character = ryu(vec![combo("a"), None, combo("b"), combo("c"), combo("d")]);
+        character.combos[2].as_mut().unwrap().deleted = true;
+        let mut old = at(&character, 4);
+        old.key.clear();
+        let mut collections = Collections {
+            favourites: vec![at(&character, 3), at(&character, 2), old],
+            ..Collections::default()
+        };
+        collections.get_or_create("set").combos = vec![at(&character, 0), at(&character, 3)];
+        // what the app does on load, the old ref gets its key before indices change
+        collections.resolve("sf", &character);
+        character.compact();
+        assert!(collections.resolve("sf", &character));
+        // b went with the compact, the rest point at the same combos as before
+        assert_eq!(keys(&collections.favourites), vec![("c", 1), ("d", 2)]);
+        assert_eq!(
+            keys(&collections.find("set").unwrap().combos),
+            vec![("a", 0), ("c", 1)]
+        );
+        for c in collections.favourites.iter() {
+            assert_eq!(character.combos[c.index].as_ref().unwrap().id, c.key);
+        }
+    }
+}
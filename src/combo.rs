@@ -0,0 +1,254 @@
+// The combo library as stored in input_<game>.json:
+//
+// "schema_version": 2,
+// "characters": { "<name>": { "combos": { "0": { "id", "name", "inputs", "state" }, ... } } }
+//
+// A slot can be null (removed by hand) or a combo marked "deleted", both are
+// skipped everywhere until a compact drops them and renumbers the rest.
+//
+// Older layouts are brought up to date by schema.rs when files are read.
+
+use indexmap::IndexMap;
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::Value;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Character {
+    pub name: String,
+    pub combos: Vec<Option<Combo>>,
+}
+impl Character {
+    pub fn new(name: String, combos: Vec<Option<Combo>>) -> Self {
+        Self { name, combos }
+    }
+
+    // index of a combo given as a number or, failing that, by name (any case)
+    pub fn find_combo(&self, query: &str) -> Option<usize> {
+        match query.trim().parse::<usize>() {
+            Ok(index) => self.live_combo(index).map(|_| index),
+            Err(_) => self
+                .live()
+                .find(|(_, c)| c.name.eq_ignore_ascii_case(query.trim()))
+                .map(|(index, _)| index),
+        }
+    }
+
+    // the combo at `index` unless the slot is null or deleted
+    pub fn live_combo(&self, index: usize) -> Option<&Combo> {
+        self.combos.get(index)?.as_ref().filter(|c| !c.deleted)
+    }
+
+    // combos that aren't null or deleted, with their indices
+    pub fn live(&self) -> impl Iterator<Item = (usize, &Combo)> {
+        self.combos
+            .iter()
+            .enumerate()
+            .filter_map(|(i, c)| c.as_ref().filter(|c| !c.deleted).map(|c| (i, c)))
+    }
+
+    // Drops null and deleted slots, returning (old index, new index) of every
+    // combo that stayed so anything pointing at them can follow.
+    pub fn compact(&mut self) -> Vec<(usize, usize)> {
+        let mut moved = Vec::new();
+        let mut kept = Vec::new();
+        for (old, combo) in std::mem::take(&mut self.combos).into_iter().enumerate() {
+            if let Some(combo) = combo.filter(|c| !c.deleted) {
+                moved.push((old, kept.len()));
+                kept.push(Some(combo));
+            }
+        }
+        self.combos = kept;
+        moved
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Combo {
+    // what copies of a library are merged by, combos from before ids had
+    // none and go by their index (see library.rs)
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub id: String,
+    pub name: String,
+    pub inputs: String,
+    pub state: ComboState,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub notes: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    // worked out from the inputs (estimated damage, meter...), not typed in
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub metadata: IndexMap<String, Value>,
+    // deleted in the app but kept until a compact, so it can be restored
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub deleted: bool,
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+impl Combo {
+    pub fn new(name: String, inputs: String, state: ComboState) -> Self {
+        Self {
+            id: new_id(),
+            name,
+            inputs,
+            state,
+            notes: String::new(),
+            tags: Vec::new(),
+            metadata: IndexMap::new(),
+            deleted: false,
+        }
+    }
+
+    // What merges and stats know the combo by: its id, or its slot in the
+    // character (`index`) for combos from before ids.
+    pub fn key(&self, index: usize) -> String {
+        if self.id.is_empty() {
+            format!("#{}", index)
+        } else {
+            self.id.clone()
+        }
+    }
+}
+
+// unique enough to tell combos apart between teammates' copies
+pub fn new_id() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0);
+    let count = COUNTER.fetch_add(1, Ordering::Relaxed);
+    format!("{:016x}", nanos.wrapping_mul(0x9e37_79b9_7f4a_7c15) ^ count)
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum ComboState {
+    NotDone,
+    Done,
+    Testing,
+}
+
+impl ComboState {
+    pub const ALL: [ComboState; 3] = [ComboState::NotDone, ComboState::Done, ComboState::Testing];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ComboState::NotDone => "NotDone",
+            ComboState::Done => "Done",
+            ComboState::Testing => "Testing",
+        }
+    }
+
+    // lenient, "not done", "not-done" and "NotDone" are all fine
+    pub fn parse(s: &str) -> Option<Self> {
+        let s: String = s
+            .chars()
+            .filter(|c| c.is_alphanumeric())
+            .collect::<String>()
+            .to_ascii_lowercase();
+        ComboState::ALL
+            .into_iter()
+            .find(|state| state.name().to_ascii_lowercase() == s)
+    }
+}
+
+// Every character in a game file, combos in index order.
+pub fn characters_from_json(json: &Value) -> Result<Vec<Character>, String> {
+    let characters = json
+        .get("characters")
+        .and_then(|c| c.as_object())
+        .ok_or("no \"characters\" object")?;
+    let mut list = Vec::new();
+    for (name, character) in characters.iter() {
+        let combos = character
+            .get("combos")
+            .and_then(|c| c.as_object())
+            .ok_or_else(|| format!("{} has no \"combos\" object", name))?;
+        // keys are indices, but the map sorts them as strings ("10" < "2")
+        let mut entries: Vec<(&String, &Value)> = combos.iter().collect();
+        entries.sort_by_key(|(k, _)| k.parse::<usize>().unwrap_or(usize::MAX));
+        let mut character_combos = Vec::new();
+        for (index, combo) in entries {
+            let combo: Option<Combo> = serde_json::from_value(combo.clone())
+                .map_err(|e| format!("{} combo {}: {}", name, index, e))?;
+            character_combos.push(combo);
+        }
+        list.push(Character::new(name.to_owned(), character_combos));
+    }
+    Ok(list)
+}
+
+// Writes `character` into the game json, adding it if it's new.
+pub fn set_character(json: &mut Value, character: &Character) {
+    let mut combos_map = IndexMap::new();
+    for (i, combo) in character.combos.iter().enumerate() {
+        combos_map.insert(i.to_string(), combo.clone());
+    }
+    json["characters"][&character.name]["combos"] = serde_json::to_value(&combos_map).unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn combo(name: &str, deleted: bool) -> Option<Combo> {
+        let mut combo = Combo::new(name.to_owned(), "5LP".to_owned(), ComboState::NotDone);
+        combo.deleted = deleted;
+        Some(combo)
+    }
+
+    // live: a (0), c (3), e (5)
+    fn character() -> Character {
+        Character::new(
+            "Ryu".to_owned(),
+            vec![
+                combo("a", false),
+                None,
+                combo("b", true),
+                combo("c", false),
+                combo("d", true),
+                combo("e", false),
+            ],
+        )
+    }
+
+    #[test]
+    fn live_skips_tombstones() {
+        let character = character();
+        let live: Vec<usize> = character.live().map(|(i, _)| i).collect();
+        assert_eq!(live, vec![0, 3, 5]);
+        assert!(character.live_combo(1).is_none());
+        assert!(character.live_combo(2).is_none());
+        assert_eq!(character.live_combo(3).unwrap().name, "c");
+    }
+
+    #[test]
+    fn find_combo_by_index_or_name() {
+        let character = character();
+        assert_eq!(character.find_combo("3"), Some(3));
+        assert_eq!(character.find_combo(" E "), Some(5));
+        assert_eq!(character.find_combo("2"), None);
+        assert_eq!(character.find_combo("b"), None);
+        assert_eq!(character.find_combo("9"), None);
+    }
+
+    #[test]
+    fn compact_renumbers() {
+        let mut character = character();
+        let ids: Vec<String> = character.live().map(|(_, c)| c.id.clone()).collect();
+        assert_eq!(character.compact(), vec![(0, 0), (3, 1), (5, 2)]);
+        let names: Vec<&str> = character.live().map(|(_, c)| c.name.as_str()).collect();
+        assert_eq!(names, vec!["a", "c", "e"]);
+        assert_eq!(character.combos.len(), 3);
+        // ids travel with their combos
+        let after: Vec<String> = character.live().map(|(_, c)| c.id.clone()).collect();
+        assert_eq!(after, ids);
+        assert_eq!(character.find_combo("e"), Some(2));
+        assert_eq!(character.compact(), vec![(0, 0), (1, 1), (2, 2)]);
+    }
+}
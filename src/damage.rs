@@ -0,0 +1,206 @@
+// Estimated damage and meter for a combo from the frame data's per-move
+// values and the game's scaling rules, set in the game file:
+//
+// "damage_scaling": { "model": "proration", "per_hit": 0.9, "min": 0.1 }
+// "damage_scaling": { "model": "skullgirls" }
+//
+// Estimates assume every hit lands and ignore counter hits and guts.
+
+use crate::combo::Combo;
+use crate::frame_data::{self, FrameData};
+use crate::profile::Profile;
+use crate::recorder::{self, Move};
+use serde_json::Value;
+
+pub trait ScalingModel {
+    // damage multiplier for hit number `hit` (0 based) of the combo, from a
+    // move of `kind`
+    fn multiplier(&self, hit: usize, kind: &str) -> f32;
+}
+
+// every hit after the first `after` is worth `per_hit` times the one before
+pub struct Proration {
+    pub per_hit: f32,
+    pub min: f32,
+    pub after: usize,
+}
+
+impl ScalingModel for Proration {
+    fn multiplier(&self, hit: usize, _kind: &str) -> f32 {
+        let scaled = hit.saturating_sub(self.after) as i32;
+        self.per_hit.powi(scaled).max(self.min)
+    }
+}
+
+// Skullgirls: full damage for the first three hits, then 87.5% of the previous
+// hit, down to 20% (27.5% for supers)
+pub struct Skullgirls;
+
+impl ScalingModel for Skullgirls {
+    fn multiplier(&self, hit: usize, kind: &str) -> f32 {
+        let min = if kind.eq_ignore_ascii_case("super") {
+            0.275
+        } else {
+            0.2
+        };
+        0.875f32.powi(hit.saturating_sub(3) as i32).max(min)
+    }
+}
+
+// the game's model, generic proration when the game doesn't say
+pub fn model(game: &Value) -> Result<Box<dyn ScalingModel>, String> {
+    let config = &game["damage_scaling"];
+    let number = |key: &str, default: f32| config[key].as_f64().map_or(default, |v| v as f32);
+    match config["model"].as_str().unwrap_or("proration") {
+        "skullgirls" => Ok(Box::new(Skullgirls)),
+        "proration" => Ok(Box::new(Proration {
+            per_hit: number("per_hit", 0.9),
+            min: number("min", 0.1),
+            after: number("after", 1.0) as usize,
+        })),
+        other => Err(format!("unknown damage scaling model '{}'", other)),
+    }
+}
+
+// Skullgirls bursts any combo past this much undizzy
+pub const UNDIZZY_MAX: u32 = 240;
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Estimate {
+    pub damage: u32,
+    pub hits: u32,
+    pub meter: i32,
+    pub undizzy: u32,
+    // moves without frame data or a damage value, counted as 0 damage
+    pub missing: Vec<String>,
+}
+
+pub fn estimate(moves: &[Move], data: &FrameData, model: &dyn ScalingModel) -> Estimate {
+    let mut estimate = Estimate::default();
+    let mut damage = 0.0;
+    for m in moves {
+        let move_data = match frame_data::lookup(data, m) {
+            Some(move_data) => move_data,
+            None => {
+                estimate.missing.push(frame_data::move_key(m));
+                continue;
+            }
+        };
+        if move_data.damage.is_none() {
+            estimate.missing.push(frame_data::move_key(m));
+        }
+        let hits = move_data.hits.unwrap_or(1).max(1);
+        let per_hit = move_data.damage.unwrap_or(0) as f32 / hits as f32;
+        for _ in 0..hits {
+            damage += per_hit * model.multiplier(estimate.hits as usize, &move_data.kind);
+            estimate.hits += 1;
+            estimate.undizzy += move_data.undizzy;
+        }
+        estimate.meter += move_data.meter;
+    }
+    estimate.damage = damage.round() as u32;
+    estimate
+}
+
+// the estimate for `inputs` by `character`, None when there's no frame data
+pub fn for_inputs(
+    profile: &Profile,
+    character: &str,
+    inputs: &str,
+) -> Result<Option<Estimate>, String> {
+    let data = frame_data::character_frame_data(profile.game, character)?;
+    if data.is_empty() {
+        return Ok(None);
+    }
+    let lines = profile
+        .map_inputs(&inputs.replace(',', " "))
+        .map_err(|e| e.to_string())?;
+    let moves = recorder::moves(&lines, &profile.buttons());
+    let model = model(profile.game)?;
+    Ok(Some(estimate(&moves, &data, model.as_ref())))
+}
+
+// Stores the estimate in the combo's metadata, true when that changed it.
+pub fn store(combo: &mut Combo, estimate: &Estimate) -> bool {
+    // no move had any data, 0 damage would just be wrong
+    if estimate.hits == 0 {
+        return false;
+    }
+    let mut values: Vec<(&str, Value)> = vec![
+        ("damage", estimate.damage.into()),
+        ("hits", estimate.hits.into()),
+        ("meter", estimate.meter.into()),
+    ];
+    if estimate.undizzy > 0 {
+        values.push(("undizzy", estimate.undizzy.into()));
+    }
+    let mut changed = false;
+    for (key, value) in values {
+        if combo.metadata.get(key) != Some(&value) {
+            combo.metadata.insert(key.to_owned(), value);
+            changed = true;
+        }
+    }
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recorder::Link;
+
+    fn mv(button: &str) -> Move {
+        Move {
+            text: format!("5{}", button),
+            motion: vec![5],
+            buttons: vec![button.to_owned()],
+            jump: false,
+            link: Link::Link,
+        }
+    }
+
+    fn data(json: &str) -> FrameData {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn scaled() {
+        let data = data(r#"{ "5LP": { "damage": 100 }, "5HP": { "damage": 200, "hits": 2 } }"#);
+        let model = Proration {
+            per_hit: 0.5,
+            min: 0.1,
+            after: 0,
+        };
+        let estimate = estimate(&[mv("LP"), mv("HP")], &data, &model);
+        // 100 + 100 * 0.5 + 100 * 0.25
+        assert_eq!(estimate.damage, 175);
+        assert_eq!(estimate.hits, 3);
+        assert!(estimate.missing.is_empty());
+    }
+
+    #[test]
+    fn missing_damage() {
+        let data = data(r#"{ "5LP": { "damage": 100 }, "5MP": { "startup": 5 } }"#);
+        let estimate = estimate(&[mv("LP"), mv("MP"), mv("HP")], &data, &Skullgirls);
+        assert_eq!(estimate.damage, 100);
+        assert_eq!(estimate.missing, vec!["5MP", "5HP"]);
+    }
+
+    #[test]
+    fn store_skips_empty() {
+        let mut combo = Combo::new(
+            "a".to_owned(),
+            "5LP".to_owned(),
+            crate::combo::ComboState::NotDone,
+        );
+        assert!(!store(&mut combo, &Estimate::default()));
+        let estimate = Estimate {
+            damage: 100,
+            hits: 1,
+            ..Estimate::default()
+        };
+        assert!(store(&mut combo, &estimate));
+        assert!(!store(&mut combo, &estimate));
+        assert_eq!(combo.metadata["damage"], 100);
+    }
+}
@@ -0,0 +1,256 @@
+// The same route entered twice: combos whose token sequences are equal once
+// spacing, commas and ">" are ignored, or only a few tokens apart.
+
+use crate::combo::{Character, Combo, ComboState};
+use crate::profile::Profile;
+
+// links written differently that mean the same thing
+const IGNORED: [&str; 2] = [",", ">"];
+
+// the tokens that make up the route, "5LK, 5MK > 2HP" and "5LK 5MK 2HP" agree
+pub fn normalise(profile: &Profile, inputs: &str) -> Result<Vec<String>, String> {
+    let lines = profile
+        .map_inputs(&inputs.to_ascii_uppercase())
+        .map_err(|e| e.to_string())?;
+    Ok(lines
+        .into_iter()
+        .flatten()
+        .filter(|t| !t.trim().is_empty() && !IGNORED.contains(&t.as_str()))
+        .collect())
+}
+
+// Levenshtein distance counted in tokens, so "236LP" vs "236HP" is 1
+pub fn distance(a: &[String], b: &[String]) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, x) in a.iter().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, y) in b.iter().enumerate() {
+            let substitute = previous[j] + usize::from(x != y);
+            current.push(substitute.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Duplicate {
+    // combo indices, first < second
+    pub first: usize,
+    pub second: usize,
+    // 0 for an exact duplicate
+    pub distance: usize,
+}
+
+// Every pair of the character's combos at most `max_distance` tokens apart.
+// Near misses also have to be small next to the shorter route, a quarter of
+// its tokens at most, or any two short routes would count.
+pub fn find(
+    profile: &Profile,
+    character: &Character,
+    max_distance: usize,
+) -> Result<Vec<Duplicate>, String> {
+    let mut routes = Vec::new();
+    for (index, combo) in character.live() {
+        routes.push((index, normalise(profile, &combo.inputs)?));
+    }
+    let mut found = Vec::new();
+    for (i, (first, a)) in routes.iter().enumerate() {
+        for (second, b) in routes[i + 1..].iter() {
+            // can't be close enough, skip the full comparison
+            if a.len().abs_diff(b.len()) > max_distance {
+                continue;
+            }
+            let distance = distance(a, b);
+            let near = distance * 4 <= a.len().min(b.len());
+            if distance <= max_distance && (distance == 0 || near) {
+                found.push(Duplicate {
+                    first: *first,
+                    second: *second,
+                    distance,
+                });
+            }
+        }
+    }
+    Ok(found)
+}
+
+fn progress(state: ComboState) -> u8 {
+    match state {
+        ComboState::NotDone => 0,
+        ComboState::Testing => 1,
+        ComboState::Done => 2,
+    }
+}
+
+// `keep` with what `other` adds: its tags and notes, metadata `keep` doesn't
+// have, and its state if it got further
+pub fn merge(keep: &Combo, other: &Combo) -> Combo {
+    let mut merged = keep.clone();
+    if progress(other.state) > progress(keep.state) {
+        merged.state = other.state;
+    }
+    for tag in other.tags.iter() {
+        if !merged.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+            merged.tags.push(tag.to_owned());
+        }
+    }
+    if !other.notes.is_empty() && !merged.notes.contains(&other.notes) {
+        if !merged.notes.is_empty() {
+            merged.notes.push('\n');
+        }
+        merged.notes.push_str(&other.notes);
+    }
+    for (key, value) in other.metadata.iter() {
+        merged
+            .metadata
+            .entry(key.to_owned())
+            .or_insert_with(|| value.clone());
+    }
+    merged
+}
+
+// Merges `duplicate.second` into `duplicate.first` and marks it deleted, so
+// other combos keep their indices. False when either is already gone.
+pub fn merge_into(character: &mut Character, duplicate: &Duplicate) -> bool {
+    let merged = match (
+        character.live_combo(duplicate.first),
+        character.live_combo(duplicate.second),
+    ) {
+        (Some(keep), Some(other)) => merge(keep, other),
+        _ => return false,
+    };
+    character.combos[duplicate.first] = Some(merged);
+    if let Some(Some(other)) = character.combos.get_mut(duplicate.second) {
+        other.deleted = true;
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profile::with_test_profile;
+
+    fn combo(inputs: &str) -> Combo {
+        Combo::new("c".to_owned(), inputs.to_owned(), ComboState::NotDone)
+    }
+
+    fn tokens(text: &str) -> Vec<String> {
+        text.split(' ').map(String::from).collect()
+    }
+
+    #[test]
+    fn normalise_ignores_links() {
+        with_test_profile(|profile| {
+            let a = normalise(profile, "5LK, 5MK > 2HP").unwrap();
+            let b = normalise(profile, "5lk 5mk 2hp").unwrap();
+            assert_eq!(a, b);
+            assert_eq!(a, tokens("5 LK 5 MK 2 HP"));
+        });
+    }
+
+    #[test]
+    fn distances() {
+        assert_eq!(distance(&tokens("2 LK 2 MP"), &tokens("2 LK 2 MP")), 0);
+        assert_eq!(distance(&tokens("2 LK 2 MP"), &tokens("2 LK 2 HP")), 1);
+        assert_eq!(distance(&tokens("2 LK"), &tokens("2 LK 2 MP")), 2);
+        assert_eq!(distance(&[], &tokens("5 LP")), 2);
+    }
+
+    #[test]
+    fn finds_pairs() {
+        let character = Character::new(
+            "Ryu".to_owned(),
+            vec![
+                Some(combo("2LK 2MP 236LP")),
+                Some(combo("2LK, 2MP > 236LP")),
+                Some(combo("2LK 2MP 236HP")),
+                Some(combo("5HK 5HK 5HK 5HK")),
+            ],
+        );
+        let found = with_test_profile(|profile| find(profile, &character, 0)).unwrap();
+        assert_eq!(
+            found,
+            vec![Duplicate {
+                first: 0,
+                second: 1,
+                distance: 0
+            }]
+        );
+        let found = with_test_profile(|profile| find(profile, &character, 1)).unwrap();
+        assert_eq!(found.len(), 3);
+        assert!(found.iter().all(|d| d.second != 3));
+    }
+
+    #[test]
+    fn short_routes_need_to_match() {
+        // 2 tokens each and both different, not a near duplicate
+        let character = Character::new(
+            "Ryu".to_owned(),
+            vec![Some(combo("5LK")), Some(combo("2HP")), Some(combo("5LK"))],
+        );
+        let found = with_test_profile(|profile| find(profile, &character, 2)).unwrap();
+        assert_eq!(
+            found,
+            vec![Duplicate {
+                first: 0,
+                second: 2,
+                distance: 0
+            }]
+        );
+    }
+
+    #[test]
+    fn skips_deleted() {
+        let mut gone = combo("2LK 2MP 236LP");
+        gone.deleted = true;
+        let character = Character::new(
+            "Ryu".to_owned(),
+            vec![Some(combo("2LK 2MP 236LP")), None, Some(gone)],
+        );
+        assert!(with_test_profile(|profile| find(profile, &character, 0))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn merge_keeps_the_most() {
+        let mut keep = combo("2LK 2MP 236LP");
+        keep.tags = vec!["corner".to_owned()];
+        keep.notes = "hit confirm".to_owned();
+        keep.metadata.insert("damage".to_owned(), 2000.into());
+        let mut other = combo("2LK 2MP 236LP");
+        other.state = ComboState::Done;
+        other.tags = vec!["Corner".to_owned(), "bnb".to_owned()];
+        other.notes = "easy".to_owned();
+        other.metadata.insert("damage".to_owned(), 1800.into());
+        other.metadata.insert("meter".to_owned(), 50.into());
+        let merged = merge(&keep, &other);
+        assert_eq!(merged.id, keep.id);
+        assert_eq!(merged.state, ComboState::Done);
+        assert_eq!(merged.tags, vec!["corner", "bnb"]);
+        assert_eq!(merged.notes, "hit confirm\neasy");
+        assert_eq!(merged.metadata["damage"], 2000);
+        assert_eq!(merged.metadata["meter"], 50);
+        // merging again adds nothing new
+        assert_eq!(merge(&merged, &other), merged);
+    }
+
+    #[test]
+    fn merge_into_tombstones() {
+        let mut character = Character::new(
+            "Ryu".to_owned(),
+            vec![Some(combo("2LK")), Some(combo("2LK")), Some(combo("5HP"))],
+        );
+        let duplicate = Duplicate {
+            first: 0,
+            second: 1,
+            distance: 0,
+        };
+        assert!(merge_into(&mut character, &duplicate));
+        assert_eq!(character.combos.len(), 3);
+        assert!(character.combos[1].as_ref().unwrap().deleted);
+        assert!(!merge_into(&mut character, &duplicate));
+    }
+}
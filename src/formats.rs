@@ -0,0 +1,476 @@
+// Plain formats for sharing a character's combos outside the app: a Markdown
+// table for Discord/docs, CSV for spreadsheets and plain text notes. Every
+// exporter has a matching importer so a pasted route can come back in.
+
+use crate::combo::{Character, Combo, ComboState};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Markdown,
+    Csv,
+    Text,
+}
+
+impl Format {
+    pub const ALL: [Format; 3] = [Format::Markdown, Format::Csv, Format::Text];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Format::Markdown => "Markdown",
+            Format::Csv => "CSV",
+            Format::Text => "Text",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Format::Markdown => "md",
+            Format::Csv => "csv",
+            Format::Text => "txt",
+        }
+    }
+
+    pub fn from_extension(ext: &str) -> Option<Self> {
+        match ext.to_ascii_lowercase().as_str() {
+            "md" | "markdown" => Some(Format::Markdown),
+            "csv" => Some(Format::Csv),
+            "txt" | "text" => Some(Format::Text),
+            _ => None,
+        }
+    }
+}
+
+pub fn export(characters: &[Character], format: Format) -> String {
+    match format {
+        Format::Markdown => export_markdown(characters),
+        Format::Csv => export_csv(characters),
+        Format::Text => export_text(characters),
+    }
+}
+
+// Characters come back with an empty name when the text doesn't say which
+// character it's for (a bare table or CSV without the column).
+pub fn import(text: &str, format: Format) -> Result<Vec<Character>, String> {
+    let characters = match format {
+        Format::Markdown => import_markdown(text)?,
+        Format::Csv => import_csv(text)?,
+        Format::Text => import_text(text)?,
+    };
+    if characters.iter().all(|c| c.combos.is_empty()) {
+        return Err(format!("no combos found in {} text", format.name()));
+    }
+    Ok(characters)
+}
+
+fn combos(character: &Character) -> impl Iterator<Item = &Combo> {
+    character.live().map(|(_, combo)| combo)
+}
+
+// adds `combo` to the character called `name`, creating it if needed
+fn push_combo(characters: &mut Vec<Character>, name: &str, combo: Combo) {
+    match characters.iter_mut().find(|c| c.name == name) {
+        Some(character) => character.combos.push(Some(combo)),
+        None => characters.push(Character::new(name.to_owned(), vec![Some(combo)])),
+    }
+}
+
+fn parse_state(s: &str) -> ComboState {
+    ComboState::parse(s).unwrap_or(ComboState::Testing)
+}
+
+// | Name | State | Inputs |
+fn export_markdown(characters: &[Character]) -> String {
+    let mut out = String::new();
+    for character in characters {
+        out.push_str(&format!("## {}\n\n", character.name));
+        out.push_str("| Name | State | Inputs |\n| --- | --- | --- |\n");
+        for combo in combos(character) {
+            out.push_str(&format!(
+                "| {} | {} | {} |\n",
+                markdown_cell(&combo.name),
+                combo.state.name(),
+                markdown_cell(&combo.inputs.replace('\n', "<br>"))
+            ));
+        }
+        out.push('\n');
+    }
+    out
+}
+
+fn markdown_cell(s: &str) -> String {
+    s.replace('|', "\\|")
+}
+
+fn import_markdown(text: &str) -> Result<Vec<Character>, String> {
+    let mut characters: Vec<Character> = Vec::new();
+    let mut character = String::new();
+    let mut columns: Option<(usize, Option<usize>, usize)> = None;
+    for line in text.lines().map(|l| l.trim()) {
+        if line.starts_with('#') {
+            character = line.trim_start_matches('#').trim().to_owned();
+            columns = None;
+            continue;
+        }
+        if !line.starts_with('|') {
+            continue;
+        }
+        let cells = markdown_cells(line);
+        if cells
+            .iter()
+            .all(|c| c.chars().all(|c| c == '-' || c == ':'))
+        {
+            continue;
+        }
+        let (name, state, inputs) = match columns {
+            Some(columns) => columns,
+            None => {
+                // the first row of a table is the header
+                let find = |header: &str| cells.iter().position(|c| c.eq_ignore_ascii_case(header));
+                columns = Some((
+                    find("name").ok_or("markdown table has no Name column")?,
+                    find("state"),
+                    find("inputs").ok_or("markdown table has no Inputs column")?,
+                ));
+                continue;
+            }
+        };
+        let cell = |i: usize| cells.get(i).cloned().unwrap_or_default();
+        let combo = Combo::new(
+            cell(name),
+            cell(inputs).replace("<br>", "\n"),
+            state.map_or(ComboState::Testing, |i| parse_state(&cell(i))),
+        );
+        push_combo(&mut characters, &character, combo);
+    }
+    Ok(characters)
+}
+
+// splits "| a | b \| c |" into ["a", "b | c"]
+fn markdown_cells(line: &str) -> Vec<String> {
+    let mut cells = Vec::new();
+    let mut cell = String::new();
+    let mut chars = line.trim().trim_start_matches('|').chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.peek() == Some(&'|') => {
+                cell.push('|');
+                chars.next();
+            }
+            '|' => cells.push(std::mem::take(&mut cell).trim().to_owned()),
+            _ => cell.push(c),
+        }
+    }
+    if !cell.trim().is_empty() {
+        cells.push(cell.trim().to_owned());
+    }
+    cells
+}
+
+const CSV_HEADER: [&str; 4] = ["character", "name", "state", "inputs"];
+
+fn export_csv(characters: &[Character]) -> String {
+    let mut out = CSV_HEADER.join(",");
+    out.push('\n');
+    for character in characters {
+        for combo in combos(character) {
+            let row = [
+                character.name.as_str(),
+                combo.name.as_str(),
+                combo.state.name(),
+                combo.inputs.as_str(),
+            ];
+            let row: Vec<String> = row.iter().map(|field| csv_field(field)).collect();
+            out.push_str(&row.join(","));
+            out.push('\n');
+        }
+    }
+    out
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+fn import_csv(text: &str) -> Result<Vec<Character>, String> {
+    let mut rows = csv_rows(text)?.into_iter();
+    let header: Vec<String> = rows
+        .next()
+        .ok_or("empty csv")?
+        .iter()
+        .map(|h| h.trim().to_ascii_lowercase())
+        .collect();
+    let find = |name: &str| header.iter().position(|h| h == name);
+    let character = find("character");
+    let name = find("name").ok_or("csv has no name column")?;
+    let state = find("state");
+    let inputs = find("inputs").ok_or("csv has no inputs column")?;
+    let mut characters: Vec<Character> = Vec::new();
+    for row in rows {
+        if row.iter().all(|field| field.trim().is_empty()) {
+            continue;
+        }
+        let field = |i: Option<usize>| i.and_then(|i| row.get(i)).cloned().unwrap_or_default();
+        let combo = Combo::new(
+            field(Some(name)),
+            field(Some(inputs)),
+            state.map_or(ComboState::Testing, |i| parse_state(&field(Some(i)))),
+        );
+        push_combo(&mut characters, &field(character), combo);
+    }
+    Ok(characters)
+}
+
+// RFC 4180, quoted fields may hold commas, quotes ("") and newlines
+fn csv_rows(text: &str) -> Result<Vec<Vec<String>>, String> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if quoted {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    field.push('"');
+                    chars.next();
+                }
+                '"' => quoted = false,
+                _ => field.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' => quoted = true,
+            ',' => row.push(std::mem::take(&mut field)),
+            '\r' => {}
+            '\n' => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            _ => field.push(c),
+        }
+    }
+    if quoted {
+        return Err("unterminated quote in csv".to_owned());
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+// Black dahlia
+// ============
+//
+// Bnb Beginner (Testing)
+// 2LK(1) 2MP 5HK
+// JMK JHP
+fn export_text(characters: &[Character]) -> String {
+    let mut out = String::new();
+    for character in characters {
+        out.push_str(&format!(
+            "{}\n{}\n\n",
+            character.name,
+            "=".repeat(character.name.chars().count().max(3))
+        ));
+        for combo in combos(character) {
+            out.push_str(&format!(
+                "{} ({})\n{}\n\n",
+                combo.name,
+                combo.state.name(),
+                combo.inputs
+            ));
+        }
+    }
+    out
+}
+
+fn import_text(text: &str) -> Result<Vec<Character>, String> {
+    let mut characters: Vec<Character> = Vec::new();
+    let mut character = String::new();
+    let text = text.replace("\r\n", "\n");
+    for block in text.split("\n\n") {
+        let lines: Vec<&str> = block
+            .lines()
+            .map(|l| l.trim_end())
+            .filter(|l| !l.trim().is_empty())
+            .collect();
+        if lines.is_empty() {
+            continue;
+        }
+        let mut lines = &lines[..];
+        // a character heading is underlined with "="
+        if lines.len() >= 2 && lines[1].chars().all(|c| c == '=') {
+            character = lines[0].trim().to_owned();
+            lines = &lines[2..];
+        }
+        if lines.is_empty() {
+            continue;
+        }
+        let (name, state) = text_title(lines[0]);
+        let inputs = lines[1..].join("\n");
+        push_combo(&mut characters, &character, Combo::new(name, inputs, state));
+    }
+    Ok(characters)
+}
+
+// "Bnb Beginner (Testing)" -> ("Bnb Beginner", Testing)
+fn text_title(line: &str) -> (String, ComboState) {
+    let line = line.trim();
+    if let Some(open) = line.rfind('(') {
+        if line.ends_with(')') {
+            if let Some(state) = ComboState::parse(&line[open + 1..line.len() - 1]) {
+                return (line[..open].trim().to_owned(), state);
+            }
+        }
+    }
+    (line.to_owned(), ComboState::Testing)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn combo(name: &str, inputs: &str, state: ComboState) -> Option<Combo> {
+        Some(Combo::new(name.to_owned(), inputs.to_owned(), state))
+    }
+
+    fn characters() -> Vec<Character> {
+        let mut gone = combo("Gone", "5LP", ComboState::Done);
+        gone.as_mut().unwrap().deleted = true;
+        vec![
+            Character::new(
+                "Filia".to_owned(),
+                vec![
+                    combo("Bnb | corner", "2LK 2MP\nJMK JHP", ComboState::Done),
+                    None,
+                    gone,
+                    combo("Reset", "5HK XX 236LP", ComboState::NotDone),
+                ],
+            ),
+            Character::new(
+                "Black dahlia".to_owned(),
+                vec![combo("Easy", "2LK(1) 2MP", ComboState::Testing)],
+            ),
+        ]
+    }
+
+    // what a round trip has to keep
+    fn summary(characters: &[Character]) -> Vec<(String, String, String, ComboState)> {
+        characters
+            .iter()
+            .flat_map(|c| {
+                combos(c).map(|combo| {
+                    (
+                        c.name.clone(),
+                        combo.name.clone(),
+                        combo.inputs.clone(),
+                        combo.state,
+                    )
+                })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn round_trips() {
+        for format in Format::ALL {
+            let text = export(&characters(), format);
+            let back = import(&text, format).unwrap();
+            assert_eq!(summary(&back), summary(&characters()), "{}", format.name());
+            assert!(!text.contains("Gone"), "{}", format.name());
+        }
+    }
+
+    #[test]
+    fn markdown_escapes() {
+        let text = export(&characters(), Format::Markdown);
+        assert!(text.contains("| Bnb \\| corner | Done | 2LK 2MP<br>JMK JHP |"));
+        assert_eq!(
+            markdown_cells("| a | b \\| c |"),
+            vec!["a".to_owned(), "b | c".to_owned()]
+        );
+        // a bare table, no character heading and no state column
+        let back = import(
+            "| Inputs | Name |\n|---|---|\n| 2LK<br>5HP | a |",
+            Format::Markdown,
+        )
+        .unwrap();
+        assert_eq!(back[0].name, "");
+        let combo = back[0].combos[0].as_ref().unwrap();
+        assert_eq!(combo.inputs, "2LK\n5HP");
+        assert_eq!(combo.state, ComboState::Testing);
+        assert!(import("| Name | State |\n| a | Done |", Format::Markdown).is_err());
+    }
+
+    #[test]
+    fn csv_quoting() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a, b"), "\"a, b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        let tricky = vec![Character::new(
+            "Ryu".to_owned(),
+            vec![combo(
+                "\"quoted\", with comma",
+                "2LK,2MP\n5HP",
+                ComboState::Done,
+            )],
+        )];
+        let text = export(&tricky, Format::Csv);
+        assert_eq!(
+            text,
+            "character,name,state,inputs\nRyu,\"\"\"quoted\"\", with comma\",Done,\"2LK,2MP\n5HP\"\n"
+        );
+        assert_eq!(
+            summary(&import(&text, Format::Csv).unwrap()),
+            summary(&tricky)
+        );
+        assert!(import("name,inputs\n\"a,5LP", Format::Csv).is_err());
+        assert!(import("name,state\na,Done", Format::Csv).is_err());
+    }
+
+    #[test]
+    fn text_titles() {
+        assert_eq!(
+            text_title("Bnb Beginner (Testing)"),
+            ("Bnb Beginner".to_owned(), ComboState::Testing)
+        );
+        assert_eq!(
+            text_title("Bnb (not done)"),
+            ("Bnb".to_owned(), ComboState::NotDone)
+        );
+        // no state, or one the app doesn't know, is part of the name
+        assert_eq!(text_title("Bnb"), ("Bnb".to_owned(), ComboState::Testing));
+        assert_eq!(
+            text_title("Bnb (corner)"),
+            ("Bnb (corner)".to_owned(), ComboState::Testing)
+        );
+        let back = import(
+            "Filia\n=====\n\nBnb (Done)\n2LK 2MP\n\nReset\r\n5HK\r\n",
+            Format::Text,
+        )
+        .unwrap();
+        assert_eq!(
+            summary(&back),
+            vec![
+                (
+                    "Filia".to_owned(),
+                    "Bnb".to_owned(),
+                    "2LK 2MP".to_owned(),
+                    ComboState::Done
+                ),
+                (
+                    "Filia".to_owned(),
+                    "Reset".to_owned(),
+                    "5HK".to_owned(),
+                    ComboState::Testing
+                ),
+            ]
+        );
+    }
+}
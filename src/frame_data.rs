@@ -0,0 +1,273 @@
+// Optional frame data for a character's moves, kept in the game file next to
+// the combos, and a check of whether a combo's links and cancels can work.
+//
+// "characters": { "<name>": { "frame_data": {
+//     "5LK": { "startup": 4, "active": 3, "recovery": 7, "on_hit": 5,
+//              "on_block": 2, "kind": "normal", "cancels": ["normal", "special"] },
+//     "236LP": { "startup": 12, "kind": "special", "damage": 1200, "hits": 2 }
+// } } }
+//
+// `cancels` lists the kinds (or exact moves) the move can be cancelled into.
+// Any field can be left out, links and cancels that need a missing one are
+// reported as unknown rather than guessed.
+
+use crate::recorder::{Link, Move};
+use indexmap::IndexMap;
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::Value;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct MoveData {
+    pub startup: Option<u32>,
+    pub active: Option<u32>,
+    pub recovery: Option<u32>,
+    pub on_hit: Option<i32>,
+    pub on_block: Option<i32>,
+    // "normal", "special", "super"...
+    pub kind: String,
+    pub cancels: Option<Vec<String>>,
+    // unscaled damage of the whole move, split evenly over its hits
+    pub damage: Option<u32>,
+    pub hits: Option<u32>,
+    // meter gained (or spent when negative)
+    pub meter: i32,
+    // Skullgirls undizzy added per hit
+    pub undizzy: u32,
+}
+
+pub type FrameData = IndexMap<String, MoveData>;
+
+// the character's frame data, empty when the game file has none
+pub fn character_frame_data(game: &Value, character: &str) -> Result<FrameData, String> {
+    match game["characters"][character].get("frame_data") {
+        Some(data) => serde_json::from_value(data.clone())
+            .map_err(|e| format!("{}: bad frame_data ({})", character, e)),
+        None => Ok(FrameData::new()),
+    }
+}
+
+// "J" + motion + buttons, how frame data is keyed: "2LK", "JMK", "236LP+LK"
+pub fn move_key(m: &Move) -> String {
+    let motion: String = m.motion.iter().map(|d| d.to_string()).collect();
+    format!(
+        "{}{}{}",
+        if m.jump { "J" } else { "" },
+        motion,
+        m.buttons.join("+")
+    )
+}
+
+// exact key first, then with generic buttons ("2LP" falls back to "2P")
+pub fn lookup<'a>(data: &'a FrameData, m: &Move) -> Option<&'a MoveData> {
+    data.get(&move_key(m)).or_else(|| {
+        let generic = Move {
+            buttons: m
+                .buttons
+                .iter()
+                .map(|b| b.chars().last().map(String::from).unwrap_or_default())
+                .collect(),
+            ..m.clone()
+        };
+        data.get(&move_key(&generic))
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Severity {
+    // can't work as written
+    Impossible,
+    // a 1-frame link
+    Tight,
+    // no frame data to tell
+    Unknown,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finding {
+    // index of the second move of the pair
+    pub index: usize,
+    pub severity: Severity,
+    pub message: String,
+}
+
+// Checks each link and cancel between consecutive moves. A link works when
+// the first move's on-hit advantage covers the next move's startup, with
+// `on_hit - startup + 1` frames to spare.
+pub fn analyse(moves: &[Move], data: &FrameData) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    for (index, pair) in moves.windows(2).enumerate() {
+        let (first, next) = (&pair[0], &pair[1]);
+        let index = index + 1;
+        let pair_name = format!("{} -> {}", first.text, next.text);
+        let (first_data, next_data) = match (lookup(data, first), lookup(data, next)) {
+            (Some(a), Some(b)) => (a, b),
+            (a, _) => {
+                let missing = if a.is_none() { first } else { next };
+                findings.push(Finding {
+                    index,
+                    severity: Severity::Unknown,
+                    message: format!("{}: no frame data for {}", pair_name, move_key(missing)),
+                });
+                continue;
+            }
+        };
+        let unknown = |what: &str, m: &Move| Finding {
+            index,
+            severity: Severity::Unknown,
+            message: format!("{}: no {} for {}", pair_name, what, move_key(m)),
+        };
+        match next.link {
+            Link::Cancel => {
+                let cancels = match first_data.cancels.as_ref() {
+                    Some(cancels) => cancels,
+                    None => {
+                        findings.push(unknown("cancels", first));
+                        continue;
+                    }
+                };
+                let exact = cancels.iter().any(|c| *c == move_key(next));
+                let by_kind = cancels
+                    .iter()
+                    .any(|c| c.eq_ignore_ascii_case(&next_data.kind));
+                if exact || by_kind {
+                    continue;
+                }
+                if next_data.kind.is_empty() {
+                    findings.push(unknown("kind", next));
+                } else {
+                    findings.push(Finding {
+                        index,
+                        severity: Severity::Impossible,
+                        message: format!(
+                            "{}: {} can't be cancelled into a {} move",
+                            pair_name, first.text, next_data.kind
+                        ),
+                    });
+                }
+            }
+            Link::Link | Link::Start => {
+                let (on_hit, startup) = match (first_data.on_hit, next_data.startup) {
+                    (Some(on_hit), Some(startup)) => (on_hit, startup),
+                    (None, _) => {
+                        findings.push(unknown("on_hit", first));
+                        continue;
+                    }
+                    (_, None) => {
+                        findings.push(unknown("startup", next));
+                        continue;
+                    }
+                };
+                let window = on_hit - startup as i32 + 1;
+                if window <= 0 {
+                    findings.push(Finding {
+                        index,
+                        severity: Severity::Impossible,
+                        message: format!(
+                            "{}: {:+} on hit but {}f startup, {} frame(s) short",
+                            pair_name,
+                            on_hit,
+                            startup,
+                            1 - window
+                        ),
+                    });
+                } else if window == 1 {
+                    findings.push(Finding {
+                        index,
+                        severity: Severity::Tight,
+                        message: format!("{}: 1-frame link", pair_name),
+                    });
+                }
+            }
+        }
+    }
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mv(text: &str, motion: &[u8], button: &str, link: Link) -> Move {
+        Move {
+            text: text.to_owned(),
+            motion: motion.to_vec(),
+            buttons: vec![button.to_owned()],
+            jump: false,
+            link,
+        }
+    }
+
+    fn data(json: &str) -> FrameData {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn links() {
+        let moves = [
+            mv("5LK", &[5], "LK", Link::Start),
+            mv("5MP", &[5], "MP", Link::Link),
+        ];
+        let tight = data(r#"{ "5LK": { "on_hit": 5 }, "5MP": { "startup": 5 } }"#);
+        assert_eq!(analyse(&moves, &tight)[0].severity, Severity::Tight);
+        let short = data(r#"{ "5LK": { "on_hit": 3 }, "5MP": { "startup": 5 } }"#);
+        assert_eq!(analyse(&moves, &short)[0].severity, Severity::Impossible);
+        let easy = data(r#"{ "5LK": { "on_hit": 6 }, "5MP": { "startup": 5 } }"#);
+        assert!(analyse(&moves, &easy).is_empty());
+    }
+
+    #[test]
+    fn missing_fields_are_unknown() {
+        let moves = [
+            mv("5LK", &[5], "LK", Link::Start),
+            mv("5MP", &[5], "MP", Link::Link),
+        ];
+        // damage only, nothing to say about the link
+        let damage = data(r#"{ "5LK": { "damage": 300 }, "5MP": { "damage": 600 } }"#);
+        let findings = analyse(&moves, &damage);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].severity, Severity::Unknown);
+        assert!(findings[0].message.contains("on_hit"));
+        let no_startup = data(r#"{ "5LK": { "on_hit": 9 }, "5MP": {} }"#);
+        let findings = analyse(&moves, &no_startup);
+        assert_eq!(findings[0].severity, Severity::Unknown);
+        assert!(findings[0].message.contains("startup for 5MP"));
+    }
+
+    #[test]
+    fn cancels() {
+        let moves = [
+            mv("2MK", &[2], "MK", Link::Start),
+            mv("236LP", &[2, 3, 6], "LP", Link::Cancel),
+        ];
+        let ok = data(r#"{ "2MK": { "cancels": ["special"] }, "236LP": { "kind": "special" } }"#);
+        assert!(analyse(&moves, &ok).is_empty());
+        let exact = data(r#"{ "2MK": { "cancels": ["236LP"] }, "236LP": {} }"#);
+        assert!(analyse(&moves, &exact).is_empty());
+        let wrong = data(r#"{ "2MK": { "cancels": ["normal"] }, "236LP": { "kind": "special" } }"#);
+        assert_eq!(analyse(&moves, &wrong)[0].severity, Severity::Impossible);
+        let no_cancels = data(r#"{ "2MK": {}, "236LP": { "kind": "special" } }"#);
+        assert_eq!(analyse(&moves, &no_cancels)[0].severity, Severity::Unknown);
+        let no_kind = data(r#"{ "2MK": { "cancels": ["normal"] }, "236LP": {} }"#);
+        assert_eq!(analyse(&moves, &no_kind)[0].severity, Severity::Unknown);
+    }
+
+    #[test]
+    fn missing_move() {
+        let moves = [
+            mv("5LK", &[5], "LK", Link::Start),
+            mv("5HP", &[5], "HP", Link::Link),
+        ];
+        let findings = analyse(&moves, &data(r#"{ "5LK": { "on_hit": 4 } }"#));
+        assert_eq!(findings[0].severity, Severity::Unknown);
+        assert!(findings[0].message.contains("no frame data for 5HP"));
+    }
+
+    #[test]
+    fn generic_buttons() {
+        let m = mv("2LP", &[2], "LP", Link::Start);
+        let generic = data(r#"{ "2P": { "startup": 4 } }"#);
+        assert_eq!(lookup(&generic, &m).unwrap().startup, Some(4));
+    }
+}
@@ -1,2 +0,0 @@
-pub mod logic_default;
-pub mod logic_skg;
@@ -0,0 +1,348 @@
+// Icon packs: a folder (or a zip installed into one) under `images/` with a
+// `pack.json` manifest mapping input tokens to image files.
+//
+// {
+//     "name": "Skull Girls",
+//     "credits": "...",
+//     "scale": 1.0,
+//     "icons": { "LP": "Sg_lp.png", "(1)": "plink.png" }
+// }
+//
+// Games pick an ordered list of packs (`"icon_packs"` in input_<game>.json),
+// a token is looked up in each pack in turn and the first hit wins.
+
+use indexmap::IndexMap;
+use serde::Deserialize;
+use serde::Serialize;
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub const IMAGES: &str = "images";
+pub const MANIFEST: &str = "pack.json";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Manifest {
+    pub name: String,
+    #[serde(default)]
+    pub credits: String,
+    #[serde(default = "default_scale")]
+    pub scale: f32,
+    pub icons: IndexMap<String, String>,
+}
+
+fn default_scale() -> f32 {
+    1.0
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct IconPack {
+    // folder name, this is what games refer to
+    pub id: String,
+    pub dir: PathBuf,
+    pub manifest: Manifest,
+}
+
+impl IconPack {
+    pub fn load(dir: &Path) -> Result<Self, String> {
+        let manifest_path = dir.join(MANIFEST);
+        let manifest_str = fs::read_to_string(&manifest_path)
+            .map_err(|e| format!("unable to read {}: {}", manifest_path.display(), e))?;
+        let manifest: Manifest = serde_json::from_str(&manifest_str)
+            .map_err(|e| format!("bad {}: {}", manifest_path.display(), e))?;
+        let id = dir
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        Ok(Self {
+            id,
+            dir: dir.to_path_buf(),
+            manifest,
+        })
+    }
+
+    // path of the image for `token`, if the pack has one and it exists on disk
+    pub fn icon(&self, token: &str) -> Option<PathBuf> {
+        let file = self.manifest.icons.get(token)?;
+        let path = self.dir.join(file);
+        if path.is_file() {
+            Some(path)
+        } else {
+            None
+        }
+    }
+}
+
+// every folder in `images_dir` that has a manifest, sorted by id
+pub fn discover(images_dir: &Path) -> Vec<IconPack> {
+    let mut packs = Vec::new();
+    if let Ok(entries) = fs::read_dir(images_dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.join(MANIFEST).is_file() {
+                match IconPack::load(&path) {
+                    Ok(pack) => packs.push(pack),
+                    Err(e) => eprintln!("skipping icon pack: {}", e),
+                }
+            }
+        }
+    }
+    packs.sort_by(|a, b| a.id.cmp(&b.id));
+    packs
+}
+
+// first pack in `chain` that has `token`, with that pack's scale
+pub fn resolve(packs: &[IconPack], chain: &[String], token: &str) -> Option<(PathBuf, f32)> {
+    chain
+        .iter()
+        .filter_map(|id| packs.iter().find(|p| &p.id == id))
+        .find_map(|pack| pack.icon(token).map(|path| (path, pack.manifest.scale)))
+}
+
+// copy a pack folder or extract a pack zip into `images_dir`, returns the new pack id
+pub fn install(source: &Path, images_dir: &Path) -> Result<String, String> {
+    let id = source
+        .file_stem()
+        .map(|n| n.to_string_lossy().to_string())
+        .ok_or_else(|| format!("bad pack path {}", source.display()))?;
+    let dest = images_dir.join(&id);
+    if dest.exists() {
+        return Err(format!("icon pack '{}' is already installed", id));
+    }
+    if source.is_dir() {
+        if !source.join(MANIFEST).is_file() {
+            return Err(format!("{} has no {}", source.display(), MANIFEST));
+        }
+        copy_dir(source, &dest).map_err(|e| e.to_string())?;
+    } else if is_zip(source) {
+        if let Err(e) = extract_zip(source, &dest) {
+            let _ = fs::remove_dir_all(&dest);
+            return Err(e);
+        }
+    } else {
+        return Err(format!("{} is not a folder or a zip", source.display()));
+    }
+    IconPack::load(&dest)?;
+    Ok(id)
+}
+
+fn is_zip(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_ascii_lowercase())
+        .as_deref()
+        == Some("zip")
+}
+
+fn copy_dir(from: &Path, to: &Path) -> io::Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), target)?;
+        }
+    }
+    Ok(())
+}
+
+// zips made by "compress folder" wrap everything in one top level folder,
+// so everything is extracted relative to wherever the manifest sits.
+fn extract_zip(source: &Path, dest: &Path) -> Result<(), String> {
+    let file = File::open(source).map_err(|e| e.to_string())?;
+    let mut archive = zip::ZipArchive::new(file).map_err(|e| e.to_string())?;
+    let root = archive
+        .file_names()
+        .filter(|name| Path::new(name).file_name() == Some(MANIFEST.as_ref()))
+        .map(|name| {
+            Path::new(name)
+                .parent()
+                .unwrap_or(Path::new(""))
+                .to_path_buf()
+        })
+        .min_by_key(|parent| parent.components().count())
+        .ok_or_else(|| format!("{} has no {}", source.display(), MANIFEST))?;
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i).map_err(|e| e.to_string())?;
+        let relative = match entry
+            .enclosed_name()
+            .and_then(|name| name.strip_prefix(&root).ok())
+        {
+            Some(relative) => relative.to_path_buf(),
+            None => continue,
+        };
+        let target = dest.join(relative);
+        if entry.is_dir() {
+            fs::create_dir_all(&target).map_err(|e| e.to_string())?;
+        } else {
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+            }
+            let mut out = File::create(&target).map_err(|e| e.to_string())?;
+            io::copy(&mut entry, &mut out).map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profile::Profile;
+    use crate::scratch_dir;
+    use serde_json::json;
+    use std::io::Write;
+
+    // a pack folder with an empty file for each of `files`, tokens map to
+    // "<token>.png"
+    fn make_pack(dir: &Path, tokens: &[&str], files: &[&str]) {
+        fs::create_dir_all(dir).unwrap();
+        let icons: IndexMap<String, String> = tokens
+            .iter()
+            .map(|t| (t.to_string(), format!("{}.png", t)))
+            .collect();
+        let manifest = json!({ "name": "test", "scale": 0.5, "icons": icons });
+        fs::write(dir.join(MANIFEST), manifest.to_string()).unwrap();
+        for file in files {
+            fs::write(dir.join(format!("{}.png", file)), "").unwrap();
+        }
+    }
+
+    #[test]
+    fn resolve_follows_the_chain() {
+        let images = scratch_dir("resolve");
+        make_pack(&images.join("a"), &["LP", "MP"], &["LP", "MP"]);
+        make_pack(&images.join("b"), &["LP", "HP", "MP"], &["LP", "HP"]);
+        make_pack(&images.join("c"), &["HK"], &["HK"]);
+        let packs = discover(&images);
+        let ids: Vec<&str> = packs.iter().map(|p| p.id.as_str()).collect();
+        assert_eq!(ids, vec!["a", "b", "c"]);
+        let chain = |ids: &[&str]| ids.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let from = |chain: &[String], token: &str| {
+            resolve(&packs, chain, token)
+                .map(|(path, _)| path.strip_prefix(&images).unwrap().to_path_buf())
+        };
+        // first pack in the chain wins
+        assert_eq!(
+            from(&chain(&["b", "a"]), "LP"),
+            Some(PathBuf::from("b/LP.png"))
+        );
+        assert_eq!(
+            from(&chain(&["a", "b"]), "LP"),
+            Some(PathBuf::from("a/LP.png"))
+        );
+        // falls through to the next pack, also when the file is missing
+        assert_eq!(
+            from(&chain(&["a", "b"]), "HP"),
+            Some(PathBuf::from("b/HP.png"))
+        );
+        assert_eq!(
+            from(&chain(&["b", "a"]), "MP"),
+            Some(PathBuf::from("a/MP.png"))
+        );
+        // packs outside the chain and ones that aren't installed don't count
+        assert_eq!(from(&chain(&["gone", "a"]), "HK"), None);
+        assert_eq!(resolve(&packs, &chain(&["c"]), "HK").unwrap().1, 0.5);
+        fs::remove_dir_all(&images).unwrap();
+    }
+
+    #[test]
+    fn profile_falls_back_to_game_then_default() {
+        let images = scratch_dir("fallback");
+        make_pack(&images.join("a"), &["LP"], &["LP"]);
+        let packs = discover(&images);
+        let game = json!({
+            "icon_packs": ["a"],
+            "attacks": { "LP": "skg\\Sg_lp.png", "MP": "skg\\Sg_mp.png", "HP": "skip" }
+        });
+        let default = json!({ "movement": { "2": "default\\down.gif" } });
+        let profile = Profile {
+            game: &game,
+            default: &default,
+            packs: &packs,
+        };
+        assert_eq!(
+            profile.icon_path("LP").unwrap().0,
+            images.join("a").join("LP.png")
+        );
+        let game_icon = PathBuf::from(IMAGES).join("skg").join("Sg_mp.png");
+        assert_eq!(profile.icon_path("MP"), Some((game_icon, 1.0)));
+        let default_icon = PathBuf::from(IMAGES).join("default").join("down.gif");
+        assert_eq!(profile.icon_path("2"), Some((default_icon, 1.0)));
+        assert_eq!(profile.icon_path("HP"), None);
+        assert_eq!(profile.icon_path("HK"), None);
+        fs::remove_dir_all(&images).unwrap();
+    }
+
+    fn make_zip(path: &Path, files: &[&str]) {
+        let mut zip = zip::ZipWriter::new(File::create(path).unwrap());
+        let options =
+            zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Stored);
+        for name in files {
+            zip.start_file(*name, options).unwrap();
+            let contents = if name.ends_with(MANIFEST) {
+                r#"{ "name": "zipped", "icons": { "LP": "icons/lp.png" } }"#
+            } else {
+                ""
+            };
+            zip.write_all(contents.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+
+    #[test]
+    fn zip_root_is_the_shallowest_manifest() {
+        let dir = scratch_dir("zip");
+        let images = dir.join("images");
+        let source = dir.join("sf6 icons.zip");
+        make_zip(
+            &source,
+            &[
+                "__MACOSX/._pack.json",
+                "sf6/pack.json",
+                "sf6/icons/lp.png",
+                "sf6/old/pack.json",
+            ],
+        );
+        assert_eq!(install(&source, &images), Ok("sf6 icons".to_owned()));
+        let installed = images.join("sf6 icons");
+        assert!(installed.join(MANIFEST).is_file());
+        assert!(installed.join("icons").join("lp.png").is_file());
+        assert!(installed.join("old").join(MANIFEST).is_file());
+        assert!(!installed.join("__MACOSX").exists());
+        let pack = IconPack::load(&installed).unwrap();
+        assert_eq!(pack.icon("LP"), Some(installed.join("icons/lp.png")));
+        assert!(install(&source, &images)
+            .unwrap_err()
+            .contains("already installed"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn zip_without_manifest_leaves_nothing() {
+        let dir = scratch_dir("bad_zip");
+        let images = dir.join("images");
+        let source = dir.join("broken.zip");
+        make_zip(&source, &["icons/lp.png"]);
+        assert!(install(&source, &images)
+            .unwrap_err()
+            .contains("has no pack.json"));
+        assert!(!images.join("broken").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn folders_are_copied() {
+        let dir = scratch_dir("copy");
+        make_pack(&dir.join("mine"), &["LP"], &["LP"]);
+        let images = dir.join("images");
+        assert_eq!(install(&dir.join("mine"), &images), Ok("mine".to_owned()));
+        assert!(images.join("mine").join("LP.png").is_file());
+        fs::create_dir_all(dir.join("empty")).unwrap();
+        assert!(install(&dir.join("empty"), &images).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,44 @@
+// Small JSON files the app keeps beside the games, like practice stats and
+// collections. A missing file just hasn't been written yet and loads as the
+// default. One that is there but doesn't parse is an error, the caller should
+// then keep the path unset so the next save doesn't overwrite what's in it.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+
+pub fn load<T: DeserializeOwned + Default>(path: &Path) -> Result<T, String> {
+    match fs::read_to_string(path) {
+        Ok(text) => serde_json::from_str(&text)
+            .map_err(|e| format!("bad json in {}: {}", path.display(), e)),
+        Err(_) => Ok(T::default()),
+    }
+}
+
+pub fn save<T: Serialize>(path: &Path, value: &T) -> Result<(), String> {
+    let text = serde_json::to_string_pretty(value).unwrap();
+    fs::write(path, text).map_err(|e| format!("unable to write {}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn missing_is_default_and_bad_json_is_an_error() {
+        let dir = crate::scratch_dir("json_file");
+        let path = dir.join("stats.json");
+        let loaded: BTreeMap<String, u32> = load(&path).unwrap();
+        assert!(loaded.is_empty());
+
+        let saved = BTreeMap::from([("landed".to_owned(), 3)]);
+        save(&path, &saved).unwrap();
+        assert_eq!(load::<BTreeMap<String, u32>>(&path).unwrap(), saved);
+
+        fs::write(&path, "{ not json").unwrap();
+        assert!(load::<BTreeMap<String, u32>>(&path).is_err());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,36 @@
+// Everything that doesn't need a window, shared by the app and usable headless.
+
+pub mod collections;
+pub mod combo;
+pub mod damage;
+pub mod duplicates;
+pub mod formats;
+pub mod frame_data;
+pub mod icon_pack;
+pub mod json_file;
+pub mod library;
+pub mod notation;
+pub mod practice;
+pub mod profile;
+pub mod query;
+pub mod recorder;
+pub mod render;
+pub mod schema;
+pub mod search;
+pub mod session;
+pub mod settings;
+pub mod share;
+pub mod shortcuts;
+pub mod startup;
+pub mod stats;
+pub mod watcher;
+pub mod wiki;
+
+// an empty folder of its own under the system temp dir, for tests that need files
+#[cfg(test)]
+pub(crate) fn scratch_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("egui_note_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
@@ -0,0 +1,413 @@
+// More than one combo library per game: the game file itself (the personal
+// library, which also holds the profile) plus any number of others next to it,
+// named library_<game>_<name>.json and holding only "characters".
+//
+// Two edited copies of a library are merged three ways against the copy they
+// both started from, combo by combo (by id, or index for combos without one)
+// and field by field, so neither side's edits overwrite the other's.
+
+use crate::combo::{self, Character, Combo};
+use crate::schema;
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const PERSONAL: &str = "personal";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Library {
+    pub name: String,
+    pub path: PathBuf,
+}
+
+pub fn path(data_dir: &Path, game_id: &str, name: &str) -> PathBuf {
+    data_dir.join(format!("library_{}_{}.json", game_id, name))
+}
+
+// the personal library first, then the others by name
+pub fn discover(data_dir: &Path, game_id: &str) -> Vec<Library> {
+    let mut libraries = vec![Library {
+        name: PERSONAL.to_owned(),
+        path: data_dir.join(format!("input_{}.json", game_id)),
+    }];
+    let prefix = format!("library_{}_", game_id);
+    let mut others: Vec<Library> = fs::read_dir(data_dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| {
+            let file = entry.file_name().to_string_lossy().to_string();
+            let name = file.strip_prefix(&prefix)?.strip_suffix(".json")?;
+            Some(Library {
+                name: name.to_owned(),
+                path: entry.path(),
+            })
+        })
+        .collect();
+    others.sort_by(|a, b| a.name.cmp(&b.name));
+    libraries.extend(others);
+    libraries
+}
+
+// a library or game file, migrated to the current schema in memory
+pub fn load(path: &Path) -> Result<Value, String> {
+    let text = fs::read_to_string(path)
+        .map_err(|e| format!("unable to read {}: {}", path.display(), e))?;
+    let mut json: Value = serde_json::from_str(&text)
+        .map_err(|e| format!("bad json in {}: {}", path.display(), e))?;
+    schema::migrate(&mut json).map_err(|e| format!("{}: {}", path.display(), e))?;
+    Ok(json)
+}
+
+// Copies another library file (a teammate's export, a game file) in as
+// library `name`, keeping only its combos.
+pub fn import(
+    data_dir: &Path,
+    game_id: &str,
+    name: &str,
+    source: &Path,
+) -> Result<Library, String> {
+    if name.is_empty() || name == PERSONAL || name.contains(['/', '\\', '.']) {
+        return Err(format!("'{}' can't be a library name", name));
+    }
+    let json = load(source)?;
+    let characters = combo::characters_from_json(&json)?;
+    let mut library = serde_json::json!({ schema::KEY: schema::CURRENT, "characters": {} });
+    for character in characters.iter() {
+        combo::set_character(&mut library, character);
+    }
+    let path = path(data_dir, game_id, name);
+    fs::write(&path, serde_json::to_string_pretty(&library).unwrap())
+        .map_err(|e| format!("unable to write {}: {}", path.display(), e))?;
+    Ok(Library {
+        name: name.to_owned(),
+        path,
+    })
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sourced {
+    pub library: String,
+    pub index: usize,
+    pub combo: Combo,
+}
+
+// a character's combos from every library, each with where it came from
+pub fn merged_view(libraries: &[(String, Vec<Character>)], character: &str) -> Vec<Sourced> {
+    let mut view = Vec::new();
+    for (library, characters) in libraries {
+        let found = characters.iter().find(|c| c.name == character);
+        for (index, combo) in found.into_iter().flat_map(|c| c.live()) {
+            view.push(Sourced {
+                library: library.to_owned(),
+                index,
+                combo: combo.clone(),
+            });
+        }
+    }
+    view
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conflict {
+    pub character: String,
+    pub combo: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct MergeResult {
+    pub characters: Vec<Character>,
+    pub conflicts: Vec<Conflict>,
+}
+
+fn find<'a>(character: Option<&'a Character>, wanted: &str) -> Option<&'a Combo> {
+    character?
+        .combos
+        .iter()
+        .enumerate()
+        .find_map(|(i, c)| c.as_ref().filter(|c| c.key(i) == wanted))
+}
+
+// one field three ways: whoever changed it wins, ours when both did
+fn merge_field<T: PartialEq + Clone>(
+    field: &str,
+    base: Option<&T>,
+    ours: &T,
+    theirs: &T,
+    conflicts: &mut Vec<String>,
+) -> T {
+    if ours == theirs || Some(theirs) == base {
+        ours.clone()
+    } else if Some(ours) == base {
+        theirs.clone()
+    } else {
+        conflicts.push(format!("{} changed on both sides, kept ours", field));
+        ours.clone()
+    }
+}
+
+fn merge_combo(
+    base: Option<&Combo>,
+    ours: &Combo,
+    theirs: &Combo,
+    conflicts: &mut Vec<String>,
+) -> Combo {
+    let inputs = merge_field(
+        "inputs",
+        base.map(|b| &b.inputs),
+        &ours.inputs,
+        &theirs.inputs,
+        conflicts,
+    );
+    Combo {
+        id: ours.id.clone(),
+        name: merge_field(
+            "name",
+            base.map(|b| &b.name),
+            &ours.name,
+            &theirs.name,
+            conflicts,
+        ),
+        state: merge_field(
+            "state",
+            base.map(|b| &b.state),
+            &ours.state,
+            &theirs.state,
+            conflicts,
+        ),
+        notes: merge_field(
+            "notes",
+            base.map(|b| &b.notes),
+            &ours.notes,
+            &theirs.notes,
+            conflicts,
+        ),
+        deleted: merge_field(
+            "deleted",
+            base.map(|b| &b.deleted),
+            &ours.deleted,
+            &theirs.deleted,
+            conflicts,
+        ),
+        tags: merge_field(
+            "tags",
+            base.map(|b| &b.tags),
+            &ours.tags,
+            &theirs.tags,
+            conflicts,
+        ),
+        // worked out from the inputs, so it follows whichever inputs won
+        metadata: if inputs == ours.inputs {
+            ours.metadata.clone()
+        } else {
+            theirs.metadata.clone()
+        },
+        inputs,
+    }
+}
+
+// Merges `ours` and `theirs`, both edited from `base`. Our combos keep their
+// slots so indices (collections, stats) stay put, new combos of theirs go on
+// the end. Conflicting fields keep our value and are reported.
+pub fn merge3(base: &[Character], ours: &[Character], theirs: &[Character]) -> MergeResult {
+    let mut result = MergeResult::default();
+    let mut names: Vec<&String> = ours.iter().map(|c| &c.name).collect();
+    for character in theirs.iter() {
+        if !names.contains(&&character.name) {
+            names.push(&character.name);
+        }
+    }
+    for name in names {
+        let base_character = base.iter().find(|c| &c.name == name);
+        let our_character = ours.iter().find(|c| &c.name == name);
+        let their_character = theirs.iter().find(|c| &c.name == name);
+        let mut combos: Vec<Option<Combo>> = Vec::new();
+        let our_combos = our_character
+            .map(|c| c.combos.as_slice())
+            .unwrap_or_default();
+        for (index, slot) in our_combos.iter().enumerate() {
+            let ours = match slot {
+                Some(ours) => ours,
+                None => {
+                    combos.push(None);
+                    continue;
+                }
+            };
+            let wanted = ours.key(index);
+            let base_combo = find(base_character, &wanted);
+            let mut conflicts = Vec::new();
+            let merged = match (base_combo, find(their_character, &wanted)) {
+                (_, Some(theirs)) => Some(merge_combo(base_combo, ours, theirs, &mut conflicts)),
+                // they deleted it, fine unless we changed it since
+                (Some(base_combo), None) if base_combo == ours => None,
+                (Some(_), None) => {
+                    conflicts.push("deleted on their side but edited on ours, kept it".to_owned());
+                    Some(ours.clone())
+                }
+                // new on our side
+                (None, None) => Some(ours.clone()),
+            };
+            result
+                .conflicts
+                .extend(conflicts.into_iter().map(|message| Conflict {
+                    character: name.to_owned(),
+                    combo: ours.name.clone(),
+                    message,
+                }));
+            combos.push(merged);
+        }
+        let their_combos = their_character
+            .map(|c| c.combos.as_slice())
+            .unwrap_or_default();
+        for (index, theirs) in their_combos.iter().enumerate() {
+            let theirs = match theirs {
+                Some(theirs) => theirs,
+                None => continue,
+            };
+            let wanted = theirs.key(index);
+            if find(our_character, &wanted).is_some() {
+                continue;
+            }
+            match find(base_character, &wanted) {
+                // new on their side
+                None => combos.push(Some(theirs.clone())),
+                // we deleted it, fine unless they changed it since
+                Some(base_combo) if base_combo == theirs => {}
+                Some(_) => {
+                    result.conflicts.push(Conflict {
+                        character: name.to_owned(),
+                        combo: theirs.name.clone(),
+                        message: "deleted on our side but edited on theirs, kept it".to_owned(),
+                    });
+                    combos.push(Some(theirs.clone()));
+                }
+            }
+        }
+        result
+            .characters
+            .push(Character::new(name.to_owned(), combos));
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::combo::ComboState;
+
+    fn combo(id: &str, inputs: &str) -> Combo {
+        let mut combo = Combo::new(id.to_uppercase(), inputs.to_owned(), ComboState::NotDone);
+        combo.id = id.to_owned();
+        combo
+    }
+
+    fn ryu(combos: Vec<Combo>) -> Vec<Character> {
+        vec![Character::new(
+            "Ryu".to_owned(),
+            combos.into_iter().map(Some).collect(),
+        )]
+    }
+
+    fn combos(result: &MergeResult) -> Vec<Option<&Combo>> {
+        result.characters[0]
+            .combos
+            .iter()
+            .map(|c| c.as_ref())
+            .collect()
+    }
+
+    #[test]
+    fn edits_on_both_sides() {
+        let base = ryu(vec![combo("a", "2LK 2MP")]);
+        let mut ours = base[0].combos[0].clone().unwrap();
+        ours.state = ComboState::Done;
+        let mut theirs = base[0].combos[0].clone().unwrap();
+        theirs.inputs = "2LK 2MP 236LP".to_owned();
+        theirs.metadata.insert("damage".to_owned(), 1200.into());
+        let result = merge3(&base, &ryu(vec![ours]), &ryu(vec![theirs]));
+        assert!(result.conflicts.is_empty());
+        let merged = combos(&result)[0].unwrap();
+        assert_eq!(merged.state, ComboState::Done);
+        assert_eq!(merged.inputs, "2LK 2MP 236LP");
+        // metadata goes with the inputs that won
+        assert_eq!(merged.metadata["damage"], 1200);
+    }
+
+    #[test]
+    fn conflict_keeps_ours() {
+        let base = ryu(vec![combo("a", "2LK")]);
+        let result = merge3(
+            &base,
+            &ryu(vec![combo("a", "2MK")]),
+            &ryu(vec![combo("a", "2HK")]),
+        );
+        assert_eq!(combos(&result)[0].unwrap().inputs, "2MK");
+        assert_eq!(
+            result.conflicts,
+            vec![Conflict {
+                character: "Ryu".to_owned(),
+                combo: "A".to_owned(),
+                message: "inputs changed on both sides, kept ours".to_owned(),
+            }]
+        );
+    }
+
+    #[test]
+    fn deletes() {
+        let base = ryu(vec![combo("a", "2LK"), combo("b", "5HP")]);
+        // they dropped a, we dropped b, neither edited since
+        let result = merge3(
+            &base,
+            &ryu(vec![combo("a", "2LK")]),
+            &ryu(vec![combo("b", "5HP")]),
+        );
+        assert!(result.conflicts.is_empty());
+        assert_eq!(combos(&result), vec![None]);
+        // edited after the other side dropped it
+        let result = merge3(
+            &base,
+            &ryu(vec![combo("a", "2MK")]),
+            &ryu(vec![combo("b", "5HK")]),
+        );
+        assert_eq!(result.conflicts.len(), 2);
+        let inputs: Vec<&str> = combos(&result)
+            .iter()
+            .flatten()
+            .map(|c| c.inputs.as_str())
+            .collect();
+        assert_eq!(inputs, vec!["2MK", "5HK"]);
+    }
+
+    #[test]
+    fn new_combos_and_characters() {
+        let base = ryu(vec![combo("a", "2LK")]);
+        let ours = ryu(vec![combo("a", "2LK"), combo("b", "5HP")]);
+        let mut theirs = ryu(vec![combo("a", "2LK"), combo("c", "5HK")]);
+        theirs.push(Character::new(
+            "Ken".to_owned(),
+            vec![Some(combo("d", "623HP"))],
+        ));
+        let result = merge3(&base, &ours, &theirs);
+        assert!(result.conflicts.is_empty());
+        let ids: Vec<&str> = combos(&result)
+            .iter()
+            .flatten()
+            .map(|c| c.id.as_str())
+            .collect();
+        // ours keep their slots, theirs go on the end
+        assert_eq!(ids, vec!["a", "b", "c"]);
+        assert_eq!(result.characters[1].name, "Ken");
+    }
+
+    #[test]
+    fn old_combos_match_by_index() {
+        let base = ryu(vec![combo("", "2LK"), combo("", "5HP")]);
+        let mut theirs = base.clone();
+        theirs[0].combos[1].as_mut().unwrap().notes = "meaty".to_owned();
+        let result = merge3(&base, &base, &theirs);
+        assert!(result.conflicts.is_empty());
+        assert_eq!(combos(&result)[1].unwrap().notes, "meaty");
+        assert_eq!(combos(&result)[0].unwrap().inputs, "2LK");
+    }
+}
@@ -2,34 +2,96 @@
 
 use eframe::egui;
 use egui::Pos2;
-use egui::{ColorImage, Label};
 use egui_extras::RetainedImage;
-use indexmap::IndexMap;
-use regex::Regex;
-use serde::Deserialize;
-use serde::Serialize;
 use serde_json::Value;
-use std::collections::HashMap;
-use std::fs::read_to_string;
+use std::fs;
 use std::fs::File;
-use std::io::BufReader;
 use std::io::Read;
-use std::path::Path;
-use std::{default, fs};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
-pub mod games;
+use egui_note::collections::{self, Collections, ComboRef};
+use egui_note::combo::{self, Character, Combo, ComboState};
+use egui_note::damage;
+use egui_note::duplicates;
+use egui_note::formats::{self, Format};
+use egui_note::frame_data::{self, Severity};
+use egui_note::icon_pack::{self, IconPack};
+use egui_note::library;
+use egui_note::notation;
+use egui_note::practice::{Practice, Step};
+use egui_note::profile::{self, Profile};
+use egui_note::recorder::{self, InputEvent, MatchOptions, Recorder};
+use egui_note::render;
+use egui_note::schema;
+use egui_note::search;
+use egui_note::session::{self, Session};
+use egui_note::settings::{self, Settings, Theme};
+use egui_note::share;
+use egui_note::shortcuts::{self, Action};
+use egui_note::startup::{self, StartupArgs};
+use egui_note::stats::{self, Stats};
+use egui_note::watcher::Watcher;
+use egui_note::wiki;
 
-const GAME_LIST: &str = "src\\games\\game_list.json";
+// inside the data directory
+const GAME_LIST: &str = "game_list.json";
 const WIDTH: f32 = 340.0;
+// keyboard layout while recording, WASD moves
+const RECORD_DIRECTIONS: [(egui::Key, usize); 4] = [
+    (egui::Key::W, Recorder::UP),
+    (egui::Key::S, Recorder::DOWN),
+    (egui::Key::A, Recorder::LEFT),
+    (egui::Key::D, Recorder::RIGHT),
+];
+const RECORD_BUTTONS: [(egui::Key, &str); 6] = [
+    (egui::Key::U, "LP"),
+    (egui::Key::I, "MP"),
+    (egui::Key::O, "HP"),
+    (egui::Key::J, "LK"),
+    (egui::Key::K, "MK"),
+    (egui::Key::L, "HK"),
+];
 
 fn main() -> Result<(), eframe::Error> {
     // Log to stdout (if you run with `RUST_LOG=debug`).
     tracing_subscriber::fmt::init();
 
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let mut args = match startup::parse(&args) {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            println!("{}", startup::USAGE);
+            return Ok(());
+        }
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, startup::USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    let data_dir = args.data_dir.clone().unwrap_or_else(default_data_dir);
+    let session = Session::load(&data_dir.join(session::SESSION));
+    let settings = Settings::load(&data_dir.join(settings::SETTINGS));
+    // flags win, otherwise pick up the last game/character/combo
+    if args.game.is_none() && args.character.is_none() {
+        args.game = session.game.clone();
+        args.character = session.character.clone();
+        args.combo = Some(session.combo.to_string()).filter(|_| args.character.is_some());
+    }
+
     let options = eframe::NativeOptions {
-        initial_window_size: Some(egui::vec2(WIDTH, 460.0)),
-        initial_window_pos: Some(Pos2 { x: 140.0, y: 0.0 }),
-        always_on_top: true,
+        initial_window_size: session
+            .window_size
+            .or(Some(settings.default_size))
+            .map(|[w, h]| egui::vec2(w, h)),
+        initial_window_pos: session
+            .window_pos
+            .or(Some(settings.default_pos))
+            .map(|[x, y]| Pos2 { x, y }),
+        always_on_top: args.always_on_top && settings.always_on_top,
+        // so the opacity setting can show what's behind
+        transparent: true,
         drag_and_drop_support: true,
         resizable: true,
         ..Default::default()
@@ -37,43 +99,36 @@ fn main() -> Result<(), eframe::Error> {
     eframe::run_native(
         "Combo List",
         options,
-        Box::new(|_cc| Box::new(MyApp::default())),
+        Box::new(move |_cc| {
+            let mut app = MyApp {
+                data_dir,
+                settings,
+                on_top_allowed: args.always_on_top,
+                compact: args.compact || session.compact,
+                show_images: session.show_images,
+                ..Default::default()
+            };
+            if session.text_mode {
+                app.render_mode = RenderMode::Text;
+            }
+            let collections_path = app.data_dir.join(collections::COLLECTIONS);
+            match Collections::load(&collections_path) {
+                Ok(loaded) => {
+                    app.collections = loaded;
+                    app.collections_path = Some(collections_path);
+                }
+                Err(e) => app.collections_message = Some(e),
+            }
+            app.startup = Some(args);
+            Box::new(app)
+        }),
     )
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
-struct Character {
-    name: String,
-    combos: Vec<Option<Combo>>,
-}
-impl Character {
-    fn new(name: String, combos: Vec<Option<Combo>>) -> Self {
-        Self { name, combos }
-    }
-}
-
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
-struct Combo {
-    name: String,
-    inputs: String,
-    state: ComboState,
-}
-
-impl Combo {
-    fn new(name: String, inputs: String, state: ComboState) -> Self {
-        Self {
-            name,
-            inputs,
-            state,
-        }
-    }
-}
-
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
-enum ComboState {
-    NotDone,
-    Done,
-    Testing,
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum RenderMode {
+    Icons,
+    Text,
 }
 
 struct MyApp {
@@ -83,7 +138,7 @@ struct MyApp {
     combo_selector: f32,
     description: String,
     show_images: bool,
-    new_line: bool,
+    render_mode: RenderMode,
     read_game_list: bool,
     game_list: Option<Value>,
     game_selected: Option<String>,
@@ -98,11 +153,80 @@ struct MyApp {
     character_list: Option<Vec<Character>>,
     character_selected: Option<Character>,
     previous_choice: Option<String>,
+    icon_packs: Vec<IconPack>,
+    new_pack_path: String,
+    pack_message: Option<String>,
+    export_message: Option<String>,
+    library_format: Format,
+    library_text: String,
+    library_message: Option<String>,
+    share_code: String,
+    data_dir: PathBuf,
+    // HUD mode, only the current combo on a bare window
+    compact: bool,
+    // what the window was last set to, follows `compact`
+    decorated: bool,
+    // selection from the command line, applied once the game list is read
+    startup: Option<StartupArgs>,
+    settings: Settings,
+    show_settings: bool,
+    show_help: bool,
+    // opens the ADD header on the next frame (shortcut)
+    open_add: bool,
+    // set when the settings changed and need applying to the window/style
+    apply_settings: bool,
+    // false with --no-always-on-top, whatever the settings say
+    on_top_allowed: bool,
+    settings_message: Option<String>,
+    practice: Option<Practice>,
+    practice_message: Option<String>,
+    stats: Stats,
+    estimate: Option<damage::Estimate>,
+    // (start time, recorder) while recording from the keyboard
+    recording: Option<(f64, Recorder)>,
+    recorded: Vec<InputEvent>,
+    event_log_path: String,
+    record_message: Option<String>,
+    // only set once the file loaded, see json_file
+    stats_path: Option<PathBuf>,
+    // kept up to date every frame for session.json
+    window_pos: Option<Pos2>,
+    window_size: Option<egui::Vec2>,
+    search_query: String,
+    query_name: String,
+    // every game's combos, built on first search and dropped when one is saved
+    search_index: Option<search::Index>,
+    search_message: Option<String>,
+    collections: Collections,
+    // only set once the file loaded, like stats_path
+    collections_path: Option<PathBuf>,
+    collections_message: Option<String>,
+    // None for the favourites
+    collection_selected: Option<String>,
+    new_collection: String,
+    // position in the selected collection while stepping through it
+    playlist: Option<usize>,
+    // the game's other libraries, loaded when first shown
+    libraries: Option<Vec<(String, Vec<Character>)>>,
+    library_name: String,
+    library_source: String,
+    merge_base: String,
+    merge_theirs: String,
+    libraries_message: Option<String>,
+    // combos read off a wiki page, the ticked ones get added
+    wiki_text: String,
+    wiki_path: String,
+    wiki_preview: Vec<(bool, wiki::Found)>,
+    wiki_message: Option<String>,
+    // the game list and the game file, reloaded when edited outside the app
+    watcher: Watcher,
+    // changed on disk while there were edits here, waiting for the user
+    external_change: Option<PathBuf>,
+    watch_message: Option<String>,
 }
 
 impl Default for MyApp {
     fn default() -> Self {
-        let mut vec: Vec<Character> = Vec::new();
         Self {
             inputs: "2LK(1) 2MP 5HK
 jMK jHP
@@ -119,225 +243,2199 @@ HK
             new_inputs: "".to_owned(),
             combo_selector: 0.0,
 
-            get_images: true,
-            retained_images: vec![None],
-            mapped_inputs: vec![None],
-            changed_inputs: true,
-            new_line: false,
-            show_images: false,
-            read_game_list: true,
-            game_list: None,
-            game_selected: None,
-            default_json: serde_json::from_str(include_str!("games\\input_default.json")).unwrap(),
-            game_json: None,
-            game_path: None,
-            read_character_list: true,
-            character_list: Some(vec),
-            character_selected: None,
-            previous_choice: None,
+            get_images: true,
+            retained_images: vec![None],
+            mapped_inputs: vec![None],
+            changed_inputs: true,
+            show_images: false,
+            render_mode: RenderMode::Icons,
+            read_game_list: true,
+            game_list: None,
+            game_selected: None,
+            default_json: profile::default_json(),
+            game_json: None,
+            game_path: None,
+            read_character_list: true,
+            character_list: Some(Vec::new()),
+            character_selected: None,
+            previous_choice: None,
+            icon_packs: icon_pack::discover(Path::new(icon_pack::IMAGES)),
+            new_pack_path: "".to_owned(),
+            pack_message: None,
+            export_message: None,
+            library_format: Format::Markdown,
+            library_text: "".to_owned(),
+            library_message: None,
+            share_code: "".to_owned(),
+            data_dir: default_data_dir(),
+            compact: false,
+            decorated: true,
+            startup: None,
+            settings: Settings::default(),
+            show_settings: false,
+            show_help: false,
+            open_add: false,
+            apply_settings: true,
+            on_top_allowed: true,
+            settings_message: None,
+            practice: None,
+            practice_message: None,
+            stats: Stats::default(),
+            estimate: None,
+            recording: None,
+            recorded: Vec::new(),
+            event_log_path: "".to_owned(),
+            record_message: None,
+            stats_path: None,
+            window_pos: None,
+            window_size: None,
+            search_query: "".to_owned(),
+            query_name: "".to_owned(),
+            search_index: None,
+            search_message: None,
+            collections: Collections::default(),
+            collections_path: None,
+            collections_message: None,
+            collection_selected: None,
+            new_collection: "".to_owned(),
+            playlist: None,
+            libraries: None,
+            library_name: "".to_owned(),
+            library_source: "".to_owned(),
+            merge_base: "".to_owned(),
+            merge_theirs: "".to_owned(),
+            libraries_message: None,
+            wiki_text: "".to_owned(),
+            wiki_path: "".to_owned(),
+            wiki_preview: Vec::new(),
+            wiki_message: None,
+            watcher: Watcher::new(1.0),
+            external_change: None,
+            watch_message: None,
+        }
+    }
+}
+
+fn default_data_dir() -> PathBuf {
+    Path::new("src").join("games")
+}
+
+impl MyApp {
+    fn session(&self) -> Session {
+        Session {
+            game: self.game_selected.clone(),
+            character: self.character_selected.as_ref().map(|c| c.name.clone()),
+            combo: self.combo_selector as usize,
+            show_images: self.show_images,
+            text_mode: self.render_mode == RenderMode::Text,
+            compact: self.compact,
+            window_pos: self.window_pos.map(|p| [p.x, p.y]),
+            window_size: self.window_size.map(|s| [s.x, s.y]),
+        }
+    }
+}
+
+trait RemoveQuotes {
+    fn remove_quotes(self) -> Self;
+}
+
+impl RemoveQuotes for String {
+    fn remove_quotes(self) -> Self {
+        self.replace('\"', "")
+    }
+}
+impl eframe::App for MyApp {
+    fn on_close_event(&mut self) -> bool {
+        let path = self.data_dir.join(session::SESSION);
+        if let Err(e) = self.session().save(&path) {
+            eprintln!("{}", e);
+        }
+        true
+    }
+
+    // the panel fill carries the opacity, so the window itself stays clear
+    fn clear_color(&self, _visuals: &egui::Visuals) -> [f32; 4] {
+        [0.0; 4]
+    }
+
+    fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        fn add_combo(mut c: Character, nself: &mut MyApp, new_combo: Combo) {
+            c.combos.push(Some(new_combo));
+            store_character(nself, c);
+        }
+
+        // Writes a whole character back to the game file and the in-memory
+        // lists, with each combo's damage estimate in its metadata.
+        fn store_character(nself: &mut MyApp, mut character: Character) {
+            for combo in character.combos.iter_mut().flatten() {
+                if combo.deleted {
+                    continue;
+                }
+                let profile = profile(nself);
+                if let Ok(Some(estimate)) =
+                    damage::for_inputs(&profile, &character.name, &combo.inputs)
+                {
+                    damage::store(combo, &estimate);
+                }
+            }
+            combo::set_character(nself.game_json.as_mut().unwrap(), &character);
+            if let Err(e) = save_game_json(nself) {
+                nself.watch_message = Some(e);
+            }
+            nself.search_index = None;
+            let list = nself.character_list.get_or_insert_with(Vec::new);
+            match list.iter_mut().find(|c| c.name == character.name) {
+                Some(c) => *c = character.clone(),
+                None => list.push(character.clone()),
+            }
+            if let Some(selected) = nself.character_selected.as_mut() {
+                if selected.name == character.name {
+                    *selected = character;
+                    nself.changed_inputs = true;
+                    nself.mapped_inputs.clear();
+                }
+            }
+        }
+
+        // Imported combos go to the character they name, or the selected one
+        // when the text doesn't say.
+        fn import_library(nself: &mut MyApp, text: &str, format: Format) {
+            let imported = match formats::import(text, format) {
+                Ok(imported) => imported,
+                Err(e) => {
+                    nself.library_message = Some(e);
+                    return;
+                }
+            };
+            let mut count = 0;
+            for mut character in imported {
+                if character.name.is_empty() {
+                    match nself.character_selected.as_ref() {
+                        Some(selected) => character.name = selected.name.clone(),
+                        None => {
+                            nself.library_message =
+                                Some("select a character to import into".to_owned());
+                            return;
+                        }
+                    }
+                }
+                let mut stored = nself
+                    .character_list
+                    .iter()
+                    .flatten()
+                    .find(|c| c.name == character.name)
+                    .cloned()
+                    .unwrap_or_else(|| Character::new(character.name.clone(), Vec::new()));
+                count += character.combos.len();
+                stored.combos.extend(character.combos);
+                store_character(nself, stored);
+            }
+            nself.library_message = Some(format!("imported {} combos", count));
+        }
+
+        fn export_library(nself: &mut MyApp, characters: Vec<Character>, name: &str) {
+            let format = nself.library_format;
+            let text = formats::export(&characters, format);
+            let path = Path::new(render::EXPORTS).join(format!(
+                "{}.{}",
+                render::file_stem(name),
+                format.extension()
+            ));
+            let result = fs::create_dir_all(render::EXPORTS).and_then(|_| fs::write(&path, text));
+            nself.library_message = Some(match result {
+                Ok(()) => format!("saved {}", path.display()),
+                Err(e) => e.to_string(),
+            });
+        }
+
+        fn save_game_json(nself: &mut MyApp) -> Result<(), String> {
+            let (game, path) = match (nself.game_json.as_ref(), nself.game_path.as_ref()) {
+                (Some(game), Some(path)) => (game, PathBuf::from(path)),
+                _ => return Err("no game loaded".to_owned()),
+            };
+            let formatted_json = serde_json::to_string_pretty(game).unwrap();
+            fs::write(&path, formatted_json)
+                .map_err(|e| format!("unable to save {}: {}", path.display(), e))?;
+            // our own write isn't an outside change
+            nself.watcher.seen(&path);
+            Ok(())
+        }
+
+        fn profile(nself: &MyApp) -> Profile<'_> {
+            Profile {
+                game: nself.game_json.as_ref().unwrap(),
+                default: &nself.default_json,
+                packs: &nself.icon_packs,
+            }
+        }
+
+        // icon packs the selected game uses, highest priority first
+        fn icon_chain(nself: &MyApp) -> Vec<String> {
+            match nself.game_json.as_ref() {
+                Some(_) => profile(nself).icon_chain(),
+                None => Vec::new(),
+            }
+        }
+
+        fn set_icon_chain(nself: &mut MyApp, chain: Vec<String>) {
+            nself.game_json.as_mut().unwrap()["icon_packs"] = Value::from(chain);
+            if let Err(e) = save_game_json(nself) {
+                nself.watch_message = Some(e);
+            }
+            nself.get_images = true;
+            nself.changed_inputs = true;
+            nself.mapped_inputs.clear();
+        }
+
+        fn known_tokens(nself: &MyApp) -> Vec<String> {
+            profile(nself).known_tokens()
+        }
+
+        fn icon_path(nself: &MyApp, token: &str) -> Option<(String, f32)> {
+            profile(nself)
+                .icon_path(token)
+                .map(|(path, scale)| (path.to_string_lossy().to_string(), scale))
+        }
+
+        fn token_color(nself: &MyApp, token: &str) -> Option<egui::Color32> {
+            let [r, g, b] = profile(nself).color(token)?;
+            Some(egui::Color32::from_rgb(r, g, b))
+        }
+
+        // one line of notation as coloured text, `5LK > 5MK > 2HP xx 236LP`
+        // `highlight` is the index in `tokens` of the input being practised
+        fn text_job(
+            nself: &MyApp,
+            tokens: &[String],
+            highlight: Option<usize>,
+            ui: &egui::Ui,
+        ) -> egui::text::LayoutJob {
+            let font_id = egui::TextStyle::Monospace.resolve(ui.style());
+            let text_color = ui.visuals().text_color();
+            let weak_color = ui.visuals().weak_text_color();
+            let mut job = egui::text::LayoutJob::default();
+            let words = notation::words(tokens);
+            // index in `tokens` of the token being added
+            let mut index = 0;
+            for (i, word) in words.iter().enumerate() {
+                while matches!(tokens.get(index), Some(t) if t.trim().is_empty()) {
+                    index += 1;
+                }
+                if i > 0 {
+                    let separator =
+                        if notation::is_separator(word) || notation::is_separator(&words[i - 1]) {
+                            " "
+                        } else {
+                            " > "
+                        };
+                    let format = egui::TextFormat::simple(font_id.clone(), weak_color);
+                    job.append(separator, 0.0, format);
+                }
+                if notation::is_separator(word) {
+                    let format = egui::TextFormat::simple(font_id.clone(), weak_color);
+                    job.append(&word.concat().to_ascii_lowercase(), 0.0, format);
+                    index += word.len();
+                    continue;
+                }
+                for token in word {
+                    let color = token_color(nself, token).unwrap_or(text_color);
+                    let mut format = egui::TextFormat::simple(font_id.clone(), color);
+                    if highlight == Some(index) {
+                        format.background = ui.visuals().selection.bg_fill;
+                    }
+                    job.append(token, 0.0, format);
+                    index += 1;
+                }
+            }
+            job
+        }
+
+        fn map_inputs(nself: &MyApp, inputs: &str) -> Vec<Vec<String>> {
+            profile(nself).map_inputs(inputs).unwrap()
+        }
+
+        fn export_image(nself: &mut MyApp) {
+            let game = nself.game_selected.clone().unwrap_or_default();
+            let character = nself.character_selected.as_ref();
+            let combo = character
+                .and_then(|c| c.live_combo(nself.combo_selector as usize))
+                .cloned();
+            let mut strip = render::ComboStrip {
+                title: combo.as_ref().map(|c| c.name.clone()).unwrap_or_default(),
+                metadata: vec![format!("Game: {}", game)],
+                lines: map_inputs(nself, &nself.inputs.replace(',', " ")),
+            };
+            if let Some(character) = character {
+                strip
+                    .metadata
+                    .push(format!("Character: {}", character.name));
+            }
+            if let Some(combo) = combo.as_ref() {
+                strip.metadata.push(format!("State: {:?}", combo.state));
+            }
+            let file_name = format!(
+                "{}_{}_{}.png",
+                render::file_stem(&game),
+                render::file_stem(&character.map(|c| c.name.clone()).unwrap_or_default()),
+                render::file_stem(&strip.title)
+            );
+            let path = Path::new(render::EXPORTS).join(file_name);
+            let profile = profile(nself);
+            let icon = |token: &str| profile.icon_path(token).or_else(|| profile.icon_path("_"));
+            let result = render::export_png(&path, &strip, icon, &render::ExportOptions::default());
+            nself.export_message = Some(match result {
+                Ok(()) => format!("saved {}", path.display()),
+                Err(e) => e,
+            });
+        }
+
+        fn install_icon_pack(nself: &mut MyApp, source: &Path) {
+            match icon_pack::install(source, Path::new(icon_pack::IMAGES)) {
+                Ok(id) => {
+                    nself.pack_message = Some(format!("installed icon pack '{}'", id));
+                    nself.icon_packs = icon_pack::discover(Path::new(icon_pack::IMAGES));
+                }
+                Err(e) => nself.pack_message = Some(e),
+            }
+        }
+
+        // loads the newly selected game, going back to `previous` when it
+        // can't be read
+        fn switch_game(nself: &mut MyApp, previous: Option<String>) {
+            if let Err(e) = get_character_list(nself) {
+                nself.game_selected = previous;
+                nself.watch_message = Some(e);
+                return;
+            }
+            nself.get_images = true;
+            nself.changed_inputs = true;
+            nself.libraries = None;
+            // converted with the old game's dialect
+            nself.wiki_preview.clear();
+        }
+
+        // "skg" for "Skull Girls", what files and share codes use
+        fn game_id(nself: &MyApp, game: &str) -> Option<String> {
+            nself.game_list.as_ref()?[game]
+                .as_str()
+                .map(|id| id.to_owned())
+        }
+
+        fn copy_share_code(nself: &mut MyApp, ctx: &egui::Context) {
+            let game = nself
+                .game_selected
+                .as_ref()
+                .and_then(|game| game_id(nself, game));
+            let character = nself.character_selected.as_ref();
+            let combo = character
+                .and_then(|c| c.live_combo(nself.combo_selector as usize))
+                .cloned();
+            match (game, character, combo) {
+                (Some(game), Some(character), Some(combo)) => {
+                    let code = share::encode(&share::SharedCombo {
+                        game,
+                        character: character.name.clone(),
+                        combo,
+                    });
+                    ctx.output_mut(|o| o.copied_text = code);
+                    nself.export_message = Some("share code copied".to_owned());
+                }
+                _ => nself.export_message = Some("select a combo to share".to_owned()),
+            }
+        }
+
+        // Adds the shared combo to its game and character (switching game if
+        // needed) and selects it.
+        fn paste_share_code(nself: &mut MyApp, code: &str) {
+            let shared = match share::decode(code) {
+                Ok(shared) => shared,
+                Err(e) => {
+                    nself.library_message = Some(e);
+                    return;
+                }
+            };
+            let game = nself
+                .game_list
+                .as_ref()
+                .and_then(|list| list.as_object())
+                .and_then(|list| {
+                    list.iter()
+                        .find(|(k, v)| *k != "previous_choice" && v.as_str() == Some(&shared.game))
+                })
+                .map(|(k, _)| k.to_owned());
+            let game = match game {
+                Some(game) => game,
+                None => {
+                    nself.library_message = Some(format!("unknown game '{}'", shared.game));
+                    return;
+                }
+            };
+            if nself.game_selected.as_ref() != Some(&game) {
+                let previous = nself.game_selected.replace(game);
+                switch_game(nself, previous);
+            }
+            let mut character = nself
+                .character_list
+                .iter()
+                .flatten()
+                .find(|c| c.name == shared.character)
+                .cloned()
+                .unwrap_or_else(|| Character::new(shared.character.clone(), Vec::new()));
+            let mut combo = shared.combo;
+            // a copy now, merges shouldn't pair it with the sender's combo
+            combo.id = combo::new_id();
+            combo.deleted = false;
+            nself.inputs = combo.inputs.clone();
+            character.combos.push(Some(combo));
+            nself.combo_selector = (character.combos.len() - 1) as f32;
+            nself.character_selected = Some(character.clone());
+            store_character(nself, character);
+            nself.library_message = Some(format!("added to {}", shared.character));
+        }
+
+        // The slider picking the character's combo. Null and deleted slots are
+        // still on it, shown as deleted with nothing to play.
+        fn combo_slider(nself: &mut MyApp, ui: &mut egui::Ui) {
+            let character = match nself.character_selected.as_ref() {
+                Some(character) if !character.combos.is_empty() => character,
+                _ => return,
+            };
+            let slider_size: f32 = character.combos.len() as f32 - 1.0;
+            if slider_size < nself.combo_selector {
+                nself.combo_selector = slider_size
+            };
+            let slot = character.combos[nself.combo_selector as usize].as_ref();
+            let text = match slot {
+                Some(combo) if !combo.deleted => combo.name.clone(),
+                Some(combo) => format!("{} (deleted)", combo.name),
+                None => "(deleted)".to_owned(),
+            };
+            let tombstone = matches!(slot, Some(combo) if combo.deleted);
+            let dead = !matches!(slot, Some(combo) if !combo.deleted);
+            let dead_slots = character
+                .combos
+                .iter()
+                .filter(|c| !matches!(c, Some(c) if !c.deleted))
+                .count();
+            if ui
+                .add(
+                    egui::Slider::new(&mut nself.combo_selector, 0.0..=slider_size)
+                        .step_by(1.0)
+                        .fixed_decimals(0)
+                        .text(text),
+                )
+                .changed()
+            {
+                nself.changed_inputs = true;
+                nself.mapped_inputs.clear();
+                nself.inputs = nself
+                    .character_selected
+                    .as_ref()
+                    .and_then(|c| c.live_combo(nself.combo_selector as usize))
+                    .map(|combo| combo.inputs.clone())
+                    .unwrap_or_default();
+            };
+            ui.horizontal(|ui| {
+                if tombstone && ui.button("RESTORE").clicked() {
+                    set_deleted(nself, false);
+                } else if !dead
+                    && ui
+                        .button("DELETE")
+                        .on_hover_text("hidden until restored or compacted")
+                        .clicked()
+                {
+                    set_deleted(nself, true);
+                }
+                if dead_slots > 0
+                    && ui
+                        .button("COMPACT")
+                        .on_hover_text(format!(
+                            "drop {} deleted slot(s) and renumber the rest",
+                            dead_slots
+                        ))
+                        .clicked()
+                {
+                    compact_character(nself);
+                }
+            });
+        }
+
+        // Marks the combo on screen deleted or not, it keeps its slot (and the
+        // others their indices) until the character is compacted.
+        fn set_deleted(nself: &mut MyApp, deleted: bool) {
+            let mut character = match nself.character_selected.clone() {
+                Some(character) => character,
+                None => return,
+            };
+            let index = nself.combo_selector as usize;
+            if let Some(Some(combo)) = character.combos.get_mut(index) {
+                combo.deleted = deleted;
+                let inputs = combo.inputs.clone();
+                store_character(nself, character);
+                nself.inputs = if deleted { "".to_owned() } else { inputs };
+                nself.playlist = None;
+            }
+        }
+
+        // drops the character's null and deleted slots, collections follow
+        fn compact_character(nself: &mut MyApp) {
+            let mut character = match nself.character_selected.clone() {
+                Some(character) => character,
+                None => return,
+            };
+            // collections resolved when the game loaded, so their keys
+            // are filled in and follow the combos through the renumbering
+            let moved = character.compact();
+            let game = nself.game_selected.clone().and_then(|g| game_id(nself, &g));
+            if let Some(game) = game {
+                if nself.collections.resolve(&game, &character) {
+                    save_collections(nself);
+                }
+            }
+            let current = nself.combo_selector as usize;
+            let first = character.live().next().map(|(i, _)| i);
+            store_character(nself, character);
+            nself.playlist = None;
+            match moved.iter().find(|(old, _)| *old == current) {
+                Some((_, new)) => select_combo(nself, *new),
+                None => {
+                    nself.inputs.clear();
+                    nself.combo_selector = 0.0;
+                    if let Some(first) = first {
+                        select_combo(nself, first);
+                    }
+                }
+            }
+        }
+
+        // the selected combo as icons or coloured text
+        fn combo_strip(nself: &mut MyApp, ui: &mut egui::Ui) {
+            if nself.show_images && !nself.inputs.is_empty() && nself.changed_inputs {
+                nself.changed_inputs = false;
+                nself.inputs = str::replace(&nself.inputs, ',', " ");
+                nself.mapped_inputs = map_inputs(nself, &nself.inputs)
+                    .into_iter()
+                    .map(Some)
+                    .collect();
+                update_estimate(nself);
+            } else if !nself.show_images {
+                nself.changed_inputs = true;
+                nself.mapped_inputs.clear();
+            }
+            if let Some(estimate) = nself.estimate.as_ref() {
+                let mut text = format!(
+                    "≈ {} damage, {} hits, meter {:+}",
+                    estimate.damage, estimate.hits, estimate.meter
+                );
+                if estimate.undizzy > damage::UNDIZZY_MAX {
+                    text.push_str(&format!(", undizzy {} (burstable)", estimate.undizzy));
+                }
+                ui.weak(text).on_hover_text(if estimate.missing.is_empty() {
+                    "from the frame data".to_owned()
+                } else {
+                    format!("no damage data for {}", estimate.missing.join(", "))
+                });
+            }
+            let current = nself.practice.as_ref().and_then(|p| p.current());
+            for (line, translation) in nself.mapped_inputs.iter().flatten().enumerate() {
+                let highlight = current.filter(|(l, _)| *l == line).map(|(_, i)| i);
+                if nself.render_mode == RenderMode::Text {
+                    let job = text_job(nself, translation, highlight, ui);
+                    ui.label(job);
+                    ui.separator();
+                    continue;
+                }
+                ui.horizontal_wrapped(|ui| {
+                    for (index, input) in translation.iter().enumerate() {
+                        // unknown tokens fall back to the "_" error image
+                        if let Some((path, scale)) =
+                            icon_path(nself, input).or_else(|| icon_path(nself, "_"))
+                        {
+                            if let Some(retained) = nself
+                                .retained_images
+                                .iter()
+                                .flatten()
+                                .find(|x| x.debug_name() == path)
+                            {
+                                let response =
+                                    retained.show_scaled(ui, scale * nself.settings.icon_scale);
+                                if highlight == Some(index) {
+                                    let stroke = ui.visuals().selection.stroke;
+                                    ui.painter().rect_stroke(
+                                        response.rect.expand(1.0),
+                                        2.0,
+                                        stroke,
+                                    );
+                                }
+                            }
+                        }
+                    }
+                    // add egui separator
+                });
+                ui.vertical(|ui| {
+                    ui.separator();
+                });
+            }
+        }
+
+        // --game/--character/--combo from the command line
+        fn apply_startup(nself: &mut MyApp, args: StartupArgs) {
+            if let Some(game) = args.game.as_ref() {
+                let found = nself
+                    .game_list
+                    .as_ref()
+                    .and_then(|list| list.as_object())
+                    .and_then(|list| {
+                        list.iter().find(|(k, v)| {
+                            *k != "previous_choice"
+                                && (k.eq_ignore_ascii_case(game) || v.as_str() == Some(game))
+                        })
+                    })
+                    .map(|(k, _)| k.to_owned());
+                match found {
+                    Some(found) if nself.game_selected.as_ref() != Some(&found) => {
+                        let previous = nself.game_selected.replace(found);
+                        switch_game(nself, previous);
+                    }
+                    Some(_) => {}
+                    None => eprintln!("unknown game '{}'", game),
+                }
+            }
+            let name = match args.character.as_ref() {
+                Some(name) => name,
+                None => return,
+            };
+            let character = nself
+                .character_list
+                .iter()
+                .flatten()
+                .find(|c| c.name.eq_ignore_ascii_case(name))
+                .cloned();
+            let character = match character {
+                Some(character) => character,
+                None => {
+                    eprintln!("no character '{}'", name);
+                    return;
+                }
+            };
+            nself.combo_selector = 0.0;
+            let combo = match args.combo.as_ref() {
+                Some(combo) => character.find_combo(combo).or_else(|| {
+                    eprintln!("{} has no combo '{}'", character.name, combo);
+                    None
+                }),
+                None => character.live().next().map(|(index, _)| index),
+            };
+            if let Some(combo) = combo.and_then(|index| Some((index, character.live_combo(index)?)))
+            {
+                nself.combo_selector = combo.0 as f32;
+                nself.inputs = combo.1.inputs.clone();
+            }
+            nself.previous_choice = Some(character.name.clone());
+            nself.character_selected = Some(character);
+            nself.changed_inputs = true;
+            nself.mapped_inputs.clear();
+        }
+
+        fn apply_settings(nself: &MyApp, ctx: &egui::Context, frame: &mut eframe::Frame) {
+            let settings = &nself.settings;
+            frame.set_always_on_top(settings.always_on_top && nself.on_top_allowed);
+            let mut visuals = match settings.theme {
+                Theme::Dark => egui::Visuals::dark(),
+                Theme::Light => egui::Visuals::light(),
+            };
+            let [r, g, b, _] = visuals.panel_fill.to_array();
+            let alpha = (settings.opacity * 255.0) as u8;
+            visuals.panel_fill = egui::Color32::from_rgba_unmultiplied(r, g, b, alpha);
+            ctx.set_visuals(visuals);
+            // text sizes are scaled from egui's defaults, body text is 12.5
+            let scale = settings.font_size / Settings::default().font_size;
+            let mut style = (*ctx.style()).clone();
+            style.text_styles = egui::Style::default()
+                .text_styles
+                .into_iter()
+                .map(|(text_style, mut font)| {
+                    font.size *= scale;
+                    (text_style, font)
+                })
+                .collect();
+            ctx.set_style(style);
+        }
+
+        fn settings_window(nself: &mut MyApp, ctx: &egui::Context, frame: &mut eframe::Frame) {
+            let mut open = nself.show_settings;
+            let mut changed = false;
+            egui::Window::new("SETTINGS")
+                .open(&mut open)
+                .collapsible(false)
+                .auto_sized()
+                .show(ctx, |ui| {
+                    let settings = &mut nself.settings;
+                    changed |= ui
+                        .checkbox(&mut settings.always_on_top, "Always on top")
+                        .changed();
+                    changed |= ui
+                        .add(
+                            egui::Slider::new(&mut settings.opacity, Settings::MIN_OPACITY..=1.0)
+                                .text("Opacity"),
+                        )
+                        .changed();
+                    changed |= ui
+                        .add(
+                            egui::Slider::new(&mut settings.font_size, 8.0..=32.0)
+                                .text("Font size"),
+                        )
+                        .changed();
+                    changed |= ui
+                        .add(
+                            egui::Slider::new(&mut settings.icon_scale, 0.25..=4.0)
+                                .text("Icon scale"),
+                        )
+                        .changed();
+                    ui.horizontal(|ui| {
+                        ui.label("Theme: ");
+                        for theme in Theme::ALL {
+                            changed |= ui
+                                .selectable_value(&mut settings.theme, theme, theme.name())
+                                .changed();
+                        }
+                    });
+                    ui.separator();
+                    ui.label("Window when there's no saved session:");
+                    ui.horizontal(|ui| {
+                        ui.label("Size: ");
+                        changed |= ui
+                            .add(egui::DragValue::new(&mut settings.default_size[0]))
+                            .changed();
+                        changed |= ui
+                            .add(egui::DragValue::new(&mut settings.default_size[1]))
+                            .changed();
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Position: ");
+                        changed |= ui
+                            .add(egui::DragValue::new(&mut settings.default_pos[0]))
+                            .changed();
+                        changed |= ui
+                            .add(egui::DragValue::new(&mut settings.default_pos[1]))
+                            .changed();
+                    });
+                    ui.horizontal(|ui| {
+                        if ui.button("USE CURRENT").clicked() {
+                            if let Some(size) = nself.window_size {
+                                settings.default_size = [size.x, size.y];
+                            }
+                            if let Some(pos) = nself.window_pos {
+                                settings.default_pos = [pos.x, pos.y];
+                            }
+                            changed = true;
+                        }
+                        if ui.button("MOVE THERE").clicked() {
+                            let [w, h] = settings.default_size;
+                            let [x, y] = settings.default_pos;
+                            frame.set_window_size(egui::vec2(w, h));
+                            frame.set_window_pos(Pos2 { x, y });
+                        }
+                    });
+                    ui.separator();
+                    ui.label("Practice:");
+                    changed |= ui
+                        .add(
+                            egui::Slider::new(&mut settings.practice_runs, 1..=50)
+                                .text("Clean runs to mark Done"),
+                        )
+                        .changed();
+                    changed |= ui
+                        .add(
+                            egui::Slider::new(&mut settings.practice_interval, 0.0..=3.0)
+                                .text("Seconds per input (0 waits for a key)"),
+                        )
+                        .changed();
+                    egui::CollapsingHeader::new("Shortcuts").show(ui, |ui| {
+                        egui::Grid::new("shortcuts_settings").show(ui, |ui| {
+                            for action in Action::ALL {
+                                let binding = settings
+                                    .shortcuts
+                                    .entry(action.id().to_owned())
+                                    .or_insert_with(|| action.default_binding().to_owned());
+                                ui.label(action.label());
+                                changed |= ui
+                                    .add(egui::TextEdit::singleline(binding).desired_width(90.0))
+                                    .changed();
+                                if shortcuts::parse(binding).is_none() {
+                                    ui.colored_label(egui::Color32::RED, "?")
+                                        .on_hover_text("not a key, using the default");
+                                }
+                                ui.end_row();
+                            }
+                        });
+                    });
+                    ui.separator();
+                    if ui.button("RESET").clicked() {
+                        *settings = Settings::default();
+                        changed = true;
+                    }
+                    if let Some(message) = nself.settings_message.as_ref() {
+                        ui.label(message);
+                    }
+                });
+            nself.show_settings = open;
+            if changed {
+                nself.apply_settings = true;
+                let path = nself.data_dir.join(settings::SETTINGS);
+                nself.settings_message = nself.settings.save(&path).err();
+            }
+        }
+
+        // Name and icons of the current combo with no window chrome, dragging
+        // anywhere moves the window.
+        fn hud(nself: &mut MyApp, ctx: &egui::Context, frame: &mut eframe::Frame) {
+            egui::CentralPanel::default()
+                .frame(egui::Frame::none())
+                .show(ctx, |ui| {
+                    let background = ui.interact(
+                        ui.max_rect(),
+                        egui::Id::new("hud_background"),
+                        egui::Sense::drag(),
+                    );
+                    if background.drag_started() {
+                        frame.drag_window();
+                    }
+                    background.context_menu(|ui| {
+                        let key = shortcut_text(nself, Action::ToggleHud);
+                        if ui.button(format!("Full editor ({})", key)).clicked() {
+                            nself.compact = false;
+                            ui.close_menu();
+                        }
+                    });
+                    let name = nself
+                        .character_selected
+                        .as_ref()
+                        .and_then(|c| c.live_combo(nself.combo_selector as usize))
+                        .cloned()
+                        .map(|combo| combo.name);
+                    if let Some(name) = name {
+                        ui.strong(name);
+                    }
+                    if let Some(practice) = nself.practice.as_ref() {
+                        ui.small(practice.status());
+                    }
+                    // the HUD always shows icons, the editor's choice is left alone
+                    let show_images = std::mem::replace(&mut nself.show_images, true);
+                    combo_strip(nself, ui);
+                    nself.show_images = show_images;
+                });
+        }
+
+        fn shortcut_text(nself: &MyApp, action: Action) -> String {
+            shortcuts::format(&shortcuts::binding(&nself.settings.shortcuts, action))
+        }
+
+        fn handle_shortcuts(nself: &mut MyApp, ctx: &egui::Context) {
+            // typing into a text box shouldn't flip through combos
+            if ctx.wants_keyboard_input() {
+                return;
+            }
+            for action in Action::ALL {
+                let shortcut = shortcuts::binding(&nself.settings.shortcuts, action);
+                if !ctx.input_mut(|i| i.consume_shortcut(&shortcut)) {
+                    continue;
+                }
+                match action {
+                    Action::NextCombo if nself.playlist.is_some() => step_playlist(nself, 1),
+                    Action::PrevCombo if nself.playlist.is_some() => step_playlist(nself, -1),
+                    Action::NextCombo => step_combo(nself, 1),
+                    Action::PrevCombo => step_combo(nself, -1),
+                    Action::NextCharacter => next_character(nself),
+                    Action::ToggleIcons => nself.show_images = !nself.show_images,
+                    Action::MarkDone => set_combo_state(nself, ComboState::Done),
+                    Action::MarkTesting => set_combo_state(nself, ComboState::Testing),
+                    Action::OpenAdd => {
+                        nself.compact = false;
+                        nself.show_window = true;
+                        nself.open_add = true;
+                    }
+                    Action::ToggleHud => nself.compact = !nself.compact,
+                    Action::Help => nself.show_help = !nself.show_help,
+                    Action::PracticeStep => practice_step(nself, None),
+                    Action::PracticeLanded => practice_land(nself),
+                    Action::PracticeMiss => practice_miss(nself),
+                }
+            }
+        }
+
+        fn select_combo(nself: &mut MyApp, index: usize) {
+            let inputs = nself
+                .character_selected
+                .as_ref()
+                .and_then(|c| c.live_combo(index))
+                .map(|combo| combo.inputs.clone());
+            if let Some(inputs) = inputs {
+                nself.combo_selector = index as f32;
+                nself.inputs = inputs;
+                nself.changed_inputs = true;
+                nself.mapped_inputs.clear();
+            }
+        }
+
+        // moves `step` combos along, skipping null and deleted slots and stopping at the ends
+        fn step_combo(nself: &mut MyApp, step: isize) {
+            let character = match nself.character_selected.as_ref() {
+                Some(character) => character,
+                None => return,
+            };
+            let mut index = nself.combo_selector as isize;
+            // a negative index wraps to a huge one and ends the loop too
+            loop {
+                index += step;
+                match character.combos.get(index as usize) {
+                    Some(Some(combo)) if !combo.deleted => break,
+                    Some(_) => continue,
+                    None => return,
+                }
+            }
+            select_combo(nself, index as usize);
+        }
+
+        fn next_character(nself: &mut MyApp) {
+            let list = match nself.character_list.as_ref() {
+                Some(list) if !list.is_empty() => list,
+                _ => return,
+            };
+            let next = match nself.character_selected.as_ref() {
+                Some(selected) => list
+                    .iter()
+                    .position(|c| c.name == selected.name)
+                    .map_or(0, |i| (i + 1) % list.len()),
+                None => 0,
+            };
+            let character = list[next].clone();
+            nself.previous_choice = Some(character.name.clone());
+            let first = character.live().next().map(|(index, _)| index);
+            nself.character_selected = Some(character);
+            nself.combo_selector = 0.0;
+            nself.changed_inputs = true;
+            nself.mapped_inputs.clear();
+            if let Some(first) = first {
+                select_combo(nself, first);
+            }
+        }
+
+        fn set_combo_state(nself: &mut MyApp, state: ComboState) {
+            let mut character = match nself.character_selected.clone() {
+                Some(character) => character,
+                None => return,
+            };
+            if let Some(Some(combo)) = character.combos.get_mut(nself.combo_selector as usize) {
+                if combo.deleted {
+                    return;
+                }
+                combo.state = state;
+                store_character(nself, character);
+            }
+        }
+
+        fn toggle_practice(nself: &mut MyApp) {
+            if nself.practice.take().is_some() {
+                return;
+            }
+            let lines: Vec<Vec<String>> = nself.mapped_inputs.iter().flatten().cloned().collect();
+            let practice = Practice::new(
+                &nself.inputs,
+                &lines,
+                nself.settings.practice_runs,
+                nself.settings.practice_interval,
+            );
+            if practice.positions.is_empty() {
+                nself.practice_message =
+                    Some("nothing to practice, show the combo first".to_owned());
+                return;
+            }
+            nself.practice_message = None;
+            nself.practice = Some(practice);
+        }
+
+        // Moves the practice highlight on, or with `dt` just moves its timer on
+        fn practice_step(nself: &mut MyApp, dt: Option<f32>) {
+            if let Some(practice) = nself.practice.as_mut() {
+                match dt {
+                    Some(dt) => {
+                        practice.tick(dt);
+                    }
+                    None => practice.advance(),
+                }
+            }
+        }
+
+        // the player landed the run, the only way one gets counted
+        fn practice_land(nself: &mut MyApp) {
+            let practice = match nself.practice.as_mut() {
+                Some(practice) => practice,
+                None => return,
+            };
+            match practice.land() {
+                Step::RunComplete => {
+                    nself.practice_message = Some(format!("run {} done", practice.runs));
+                    record_attempt(nself, None);
+                }
+                Step::Done => {
+                    let runs = practice.runs;
+                    record_attempt(nself, None);
+                    nself.practice = None;
+                    set_combo_state(nself, ComboState::Done);
+                    nself.practice_message = Some(format!("{} clean runs, marked Done", runs));
+                }
+            }
+        }
+
+        fn practice_miss(nself: &mut MyApp) {
+            if let Some(practice) = nself.practice.as_mut() {
+                let input = practice.step;
+                practice.miss();
+                record_attempt(nself, Some(input));
+            }
+        }
+
+        // a landed run, or a drop at practice input `dropped_at`
+        fn record_attempt(nself: &mut MyApp, dropped_at: Option<usize>) {
+            let character = match nself.character_selected.as_ref() {
+                Some(character) => character,
+                None => return,
+            };
+            let index = nself.combo_selector as usize;
+            let combo = match character.live_combo(index) {
+                Some(combo) => combo,
+                None => return,
+            };
+            let stats = nself.stats.combo_mut(&character.name, &combo.key(index));
+            match dropped_at {
+                Some(input) => stats.record_drop(stats::today(), input),
+                None => stats.record_landed(stats::today()),
+            }
+            if let Some(path) = nself.stats_path.as_ref() {
+                if let Err(e) = nself.stats.save(path) {
+                    nself.practice_message = Some(e);
+                }
+            }
+        }
+
+        fn stats_section(nself: &MyApp, ui: &mut egui::Ui) {
+            let character = nself.character_selected.as_ref();
+            let combo = character
+                .and_then(|c| c.live_combo(nself.combo_selector as usize))
+                .cloned();
+            let stats = match (character, combo.as_ref()) {
+                (Some(character), Some(combo)) => nself
+                    .stats
+                    .combo(&character.name, &combo.key(nself.combo_selector as usize)),
+                _ => None,
+            };
+            let stats = match stats {
+                Some(stats) if stats.attempts > 0 => stats,
+                _ => {
+                    ui.label("No practice runs yet.");
+                    return;
+                }
+            };
+            ui.label(format!(
+                "{} / {} landed ({:.0}%)",
+                stats.landed,
+                stats.attempts,
+                stats.success_rate().unwrap_or(0.0) * 100.0
+            ));
+            ui.label(format!(
+                "Streak {}, best {}",
+                stats.streak, stats.best_streak
+            ));
+            if let Some((input, count)) = stats.worst_drop() {
+                // name the input the same way practice counts them
+                let lines: Vec<Vec<String>> =
+                    nself.mapped_inputs.iter().flatten().cloned().collect();
+                let token = Practice::new(&nself.inputs, &lines, 1, 0.0)
+                    .positions
+                    .get(input)
+                    .and_then(|(line, index)| lines[*line].get(*index).cloned())
+                    .unwrap_or_default();
+                ui.label(format!(
+                    "Dropped most at input {} {} ({}x)",
+                    input + 1,
+                    token,
+                    count
+                ));
+            }
+            // success rate per day, x is days since the first one
+            let first = stats.days.keys().next().copied().unwrap_or(0);
+            let points: Vec<[f64; 2]> = stats
+                .days
+                .iter()
+                .filter(|(_, day)| day.attempts > 0)
+                .map(|(day, s)| {
+                    let rate = s.landed as f64 / s.attempts as f64 * 100.0;
+                    [(day - first) as f64, rate]
+                })
+                .collect();
+            egui::plot::Plot::new("success_rate")
+                .height(120.0)
+                .include_y(0.0)
+                .include_y(100.0)
+                .allow_drag(false)
+                .allow_zoom(false)
+                .show(ui, |plot_ui| {
+                    plot_ui.line(egui::plot::Line::new(egui::plot::PlotPoints::from(
+                        points.clone(),
+                    )));
+                    plot_ui.points(
+                        egui::plot::Points::new(egui::plot::PlotPoints::from(points)).radius(3.0),
+                    );
+                });
+        }
+
+        fn record_keys(nself: &mut MyApp, ctx: &egui::Context) {
+            let (start, recorder) = match nself.recording.as_mut() {
+                Some(recording) => recording,
+                None => return,
+            };
+            let (time, events) = ctx.input(|i| (i.time, i.events.clone()));
+            let frame = ((time - *start) as f32 * recorder::FPS) as u32;
+            for event in events {
+                if let egui::Event::Key {
+                    key,
+                    pressed,
+                    repeat: false,
+                    ..
+                } = event
+                {
+                    if let Some((_, direction)) = RECORD_DIRECTIONS.iter().find(|(k, _)| *k == key)
+                    {
+                        recorder.direction_key(frame, *direction, pressed);
+                    }
+                    if let Some((_, button)) = RECORD_BUTTONS.iter().find(|(k, _)| *k == key) {
+                        recorder.button(frame, button, pressed);
+                    }
+                }
+            }
+        }
+
+        // checks the last recording or loaded log against the selected combo
+        fn check_recording(nself: &mut MyApp) {
+            let profile = profile(nself);
+            let lines = match profile.map_inputs(&nself.inputs.replace(',', " ")) {
+                Ok(lines) => lines,
+                Err(e) => {
+                    nself.record_message = Some(e.to_string());
+                    return;
+                }
+            };
+            let moves = recorder::moves(&lines, &profile.buttons());
+            let report = recorder::check(&moves, &nself.recorded, &MatchOptions::default());
+            nself.record_message = Some(match report.miss {
+                Some(miss) => format!("{}/{} moves, {}", report.matched, report.total, miss),
+                None => format!("{}/{} moves, clean", report.matched, report.total),
+            });
+        }
+
+        fn recorder_section(nself: &mut MyApp, ctx: &egui::Context, ui: &mut egui::Ui) {
+            ui.label("WASD to move, U I O for LP MP HP, J K L for LK MK HK.");
+            ui.horizontal(|ui| {
+                if nself.recording.is_some() {
+                    if ui.button("STOP").clicked() {
+                        if let Some((_, recorder)) = nself.recording.take() {
+                            nself.recorded = recorder.events;
+                            check_recording(nself);
+                        }
+                    }
+                    // keep the clock going between key presses
+                    ctx.request_repaint();
+                } else if ui.button("RECORD").clicked() {
+                    nself.recording = Some((ctx.input(|i| i.time), Recorder::default()));
+                    nself.record_message = Some("recording...".to_owned());
+                }
+                if ui.button("CHECK").clicked() {
+                    check_recording(nself);
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label("Event log: ");
+                ui.text_edit_singleline(&mut nself.event_log_path);
+            });
+            ui.horizontal(|ui| {
+                let path = nself.event_log_path.clone();
+                if ui.button("LOAD").clicked() && !path.is_empty() {
+                    let loaded = fs::read_to_string(&path)
+                        .map_err(|e| e.to_string())
+                        .and_then(|text| recorder::parse_log(&text));
+                    match loaded {
+                        Ok(events) => {
+                            nself.recorded = events;
+                            check_recording(nself);
+                        }
+                        Err(e) => nself.record_message = Some(e),
+                    }
+                }
+                if ui.button("SAVE").clicked() && !path.is_empty() {
+                    nself.record_message = Some(
+                        match fs::write(&path, recorder::format_log(&nself.recorded)) {
+                            Ok(()) => format!("saved {} events", nself.recorded.len()),
+                            Err(e) => e.to_string(),
+                        },
+                    );
+                }
+            });
+            if let Some(message) = nself.record_message.as_ref() {
+                ui.label(message);
+            }
+        }
+
+        // link/cancel problems in the selected combo from the frame data
+        fn links_section(nself: &MyApp, ui: &mut egui::Ui) {
+            let (game, character) = match (&nself.game_json, &nself.character_selected) {
+                (Some(game), Some(character)) => (game, character),
+                _ => return,
+            };
+            let data = match frame_data::character_frame_data(game, &character.name) {
+                Ok(data) if data.is_empty() => {
+                    ui.label("No frame data for this character.");
+                    return;
+                }
+                Ok(data) => data,
+                Err(e) => {
+                    ui.label(e);
+                    return;
+                }
+            };
+            let profile = profile(nself);
+            let lines = match profile.map_inputs(&nself.inputs.replace(',', " ")) {
+                Ok(lines) => lines,
+                Err(_) => return,
+            };
+            let moves = recorder::moves(&lines, &profile.buttons());
+            let findings = frame_data::analyse(&moves, &data);
+            if findings.is_empty() {
+                ui.label("Every link and cancel checks out.");
+            }
+            for finding in findings {
+                let color = match finding.severity {
+                    Severity::Impossible => egui::Color32::RED,
+                    Severity::Tight => egui::Color32::YELLOW,
+                    Severity::Unknown => ui.visuals().weak_text_color(),
+                };
+                ui.colored_label(color, finding.message);
+            }
+        }
+
+        // the combo on screen, as collections refer to it
+        fn current_ref(nself: &MyApp) -> Option<ComboRef> {
+            let game = game_id(nself, nself.game_selected.as_ref()?)?;
+            let character = nself.character_selected.as_ref()?;
+            let index = nself.combo_selector as usize;
+            let combo = character.live_combo(index)?;
+            Some(ComboRef {
+                game,
+                character: character.name.clone(),
+                key: combo.key(index),
+                index,
+                name: combo.name.clone(),
+            })
+        }
+
+        fn save_collections(nself: &mut MyApp) {
+            if let Some(path) = nself.collections_path.as_ref() {
+                nself.collections_message = nself.collections.save(path).err();
+            }
+        }
+
+        // the favourites or the selected collection, in order
+        fn playlist_combos(nself: &MyApp) -> Vec<ComboRef> {
+            match nself.collection_selected.as_ref() {
+                Some(name) => nself
+                    .collections
+                    .find(name)
+                    .map(|c| c.combos.clone())
+                    .unwrap_or_default(),
+                None => nself.collections.favourites.clone(),
+            }
+        }
+
+        fn play(nself: &mut MyApp, position: usize) {
+            let combos = playlist_combos(nself);
+            let combo = match combos.get(position) {
+                Some(combo) => combo.clone(),
+                None => return,
+            };
+            apply_startup(
+                nself,
+                StartupArgs {
+                    game: Some(combo.game.clone()),
+                    character: Some(combo.character.clone()),
+                    ..Default::default()
+                },
+            );
+            // by key, in case the game file changed since the ref was made
+            let index = nself
+                .character_selected
+                .as_ref()
+                .and_then(|c| combo.find(c));
+            if let Some(index) = index {
+                select_combo(nself, index);
+            }
+            nself.playlist = Some(position);
+        }
+
+        // steps through the collection, stopping at the ends
+        fn step_playlist(nself: &mut MyApp, step: isize) {
+            let position = nself.playlist.unwrap_or(0) as isize + step;
+            if position >= 0 && (position as usize) < playlist_combos(nself).len() {
+                play(nself, position as usize);
+            }
+        }
+
+        fn collections_section(nself: &mut MyApp, ui: &mut egui::Ui) {
+            let mut changed = false;
+            ui.horizontal(|ui| {
+                let selected = nself
+                    .collection_selected
+                    .clone()
+                    .unwrap_or_else(|| "Favourites".to_owned());
+                egui::ComboBox::from_id_source("collection_select")
+                    .selected_text(selected)
+                    .show_ui(ui, |ui| {
+                        let names: Vec<Option<String>> = std::iter::once(None)
+                            .chain(
+                                nself
+                                    .collections
+                                    .collections
+                                    .iter()
+                                    .map(|c| Some(c.name.clone())),
+                            )
+                            .collect();
+                        for name in names {
+                            let label = name.clone().unwrap_or_else(|| "Favourites".to_owned());
+                            if ui
+                                .selectable_value(&mut nself.collection_selected, name, label)
+                                .changed()
+                            {
+                                nself.playlist = None;
+                            }
+                        }
+                    });
+                if let Some(name) = nself.collection_selected.clone() {
+                    if ui.button("DELETE").clicked() {
+                        nself.collections.collections.retain(|c| c.name != name);
+                        nself.collection_selected = None;
+                        nself.playlist = None;
+                        changed = true;
+                    }
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.text_edit_singleline(&mut nself.new_collection);
+                let name = nself.new_collection.trim().to_owned();
+                if ui.button("NEW").clicked() && !name.is_empty() {
+                    nself.collections.get_or_create(&name);
+                    nself.collection_selected = Some(name);
+                    nself.new_collection = "".to_owned();
+                    nself.playlist = None;
+                    changed = true;
+                }
+            });
+            let current = current_ref(nself);
+            if let (Some(current), Some(name)) = (current, nself.collection_selected.clone()) {
+                if ui.button("ADD CURRENT").clicked() {
+                    changed |= nself.collections.get_or_create(&name).add(current);
+                }
+            }
+            let combos = playlist_combos(nself);
+            if combos.is_empty() {
+                ui.label("Nothing here yet.");
+            }
+            let mut play_at = None;
+            let mut remove = None;
+            let mut shift = None;
+            for (i, combo) in combos.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    let playing = nself.playlist == Some(i);
+                    if ui.selectable_label(playing, combo.label()).clicked() {
+                        play_at = Some(i);
+                    }
+                    if ui.small_button("⬆").clicked() {
+                        shift = Some((i, -1));
+                    }
+                    if ui.small_button("⬇").clicked() {
+                        shift = Some((i, 1));
+                    }
+                    if ui.small_button("x").clicked() {
+                        remove = Some(i);
+                    }
+                });
+            }
+            let list = match nself.collection_selected.clone() {
+                Some(name) => &mut nself.collections.get_or_create(&name).combos,
+                None => &mut nself.collections.favourites,
+            };
+            if let Some(i) = remove {
+                list.remove(i);
+                nself.playlist = None;
+                changed = true;
+            }
+            if let Some((i, step)) = shift {
+                let to = i as isize + step;
+                if to >= 0 && (to as usize) < list.len() {
+                    list.swap(i, to as usize);
+                    nself.playlist = None;
+                    changed = true;
+                }
+            }
+            if changed {
+                save_collections(nself);
+            }
+            ui.horizontal(|ui| {
+                if ui.button("PREV").clicked() {
+                    step_playlist(nself, -1);
+                }
+                if ui.button("PLAY").clicked() {
+                    play_at = Some(0);
+                }
+                if ui.button("NEXT").clicked() {
+                    step_playlist(nself, 1);
+                }
+                if nself.playlist.is_some() && ui.button("STOP").clicked() {
+                    nself.playlist = None;
+                }
+            });
+            if let Some(position) = nself.playlist {
+                ui.label(format!(
+                    "{}/{}, arrow keys step through",
+                    position + 1,
+                    combos.len()
+                ));
+            }
+            if let Some(i) = play_at {
+                play(nself, i);
+            }
+            if let Some(message) = nself.collections_message.as_ref() {
+                ui.label(message);
+            }
+        }
+
+        fn load_libraries(nself: &mut MyApp, game_id: &str) -> Vec<(String, Vec<Character>)> {
+            let mut loaded = Vec::new();
+            let mut errors = Vec::new();
+            for found in library::discover(&nself.data_dir, game_id)
+                .into_iter()
+                .skip(1)
+            {
+                match library::load(&found.path).and_then(|json| combo::characters_from_json(&json))
+                {
+                    Ok(characters) => loaded.push((found.name, characters)),
+                    Err(e) => errors.push(e),
+                }
+            }
+            if !errors.is_empty() {
+                nself.libraries_message = Some(errors.join("\n"));
+            }
+            loaded
+        }
+
+        // Ours is the game file as it is now, theirs and the base they both
+        // started from are other copies of it.
+        fn merge_library(nself: &mut MyApp) -> Result<Vec<library::Conflict>, String> {
+            let read = |path: &str| {
+                library::load(Path::new(path.trim()))
+                    .and_then(|json| combo::characters_from_json(&json))
+            };
+            let base = read(&nself.merge_base)?;
+            let theirs = read(&nself.merge_theirs)?;
+            let ours = combo::characters_from_json(nself.game_json.as_ref().ok_or("no game")?)?;
+            let merged = library::merge3(&base, &ours, &theirs);
+            for character in merged.characters.iter() {
+                combo::set_character(nself.game_json.as_mut().unwrap(), character);
+            }
+            save_game_json(nself)?;
+            nself.search_index = None;
+            get_character_list(nself)?;
+            Ok(merged.conflicts)
+        }
+
+        fn libraries_section(nself: &mut MyApp, ui: &mut egui::Ui) {
+            let game_id = match nself.game_selected.clone().and_then(|g| game_id(nself, &g)) {
+                Some(game_id) => game_id,
+                None => return,
+            };
+            if nself.libraries.is_none() {
+                nself.libraries = Some(load_libraries(nself, &game_id));
+            }
+            let mut sources = vec![(
+                library::PERSONAL.to_owned(),
+                nself.character_list.clone().unwrap_or_default(),
+            )];
+            sources.extend(nself.libraries.clone().unwrap_or_default());
+            ui.label(format!(
+                "Libraries: {}",
+                sources
+                    .iter()
+                    .map(|(name, _)| name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+            if let Some(character) = nself.character_selected.clone() {
+                let mut copy = None;
+                for sourced in library::merged_view(&sources, &character.name) {
+                    ui.horizontal(|ui| {
+                        ui.label(format!("[{}]", sourced.library));
+                        if ui
+                            .selectable_label(false, &sourced.combo.name)
+                            .on_hover_text(&sourced.combo.inputs)
+                            .clicked()
+                        {
+                            nself.inputs = sourced.combo.inputs.clone();
+                            nself.changed_inputs = true;
+                            nself.mapped_inputs.clear();
+                        }
+                        if sourced.library != library::PERSONAL
+                            && ui
+                                .small_button("COPY")
+                                .on_hover_text("add to the personal library")
+                                .clicked()
+                        {
+                            copy = Some(sourced.combo.clone());
+                        }
+                    });
+                }
+                if let Some(mut combo) = copy {
+                    // its own combo now, merges shouldn't pair it with the original
+                    combo.id = combo::new_id();
+                    let mut character = character;
+                    character.combos.push(Some(combo));
+                    store_character(nself, character);
+                }
+            }
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.label("Name: ");
+                ui.text_edit_singleline(&mut nself.library_name);
+            });
+            ui.horizontal(|ui| {
+                ui.label("File: ");
+                ui.text_edit_singleline(&mut nself.library_source)
+                    .on_hover_text("a game file or another library to add as its own library");
+                if ui.button("ADD LIBRARY").clicked() {
+                    let source = PathBuf::from(nself.library_source.trim());
+                    let name = nself.library_name.trim().to_owned();
+                    match library::import(&nself.data_dir, &game_id, &name, &source) {
+                        Ok(added) => {
+                            nself.libraries_message = Some(format!("added library {}", added.name));
+                            nself.libraries = None;
+                            nself.library_name = "".to_owned();
+                            nself.library_source = "".to_owned();
+                        }
+                        Err(e) => nself.libraries_message = Some(e),
+                    }
+                }
+            });
+            ui.separator();
+            ui.label("Three-way merge into the personal library:");
+            ui.horizontal(|ui| {
+                ui.label("Base: ");
+                ui.text_edit_singleline(&mut nself.merge_base)
+                    .on_hover_text("the copy both sides started from");
+            });
+            ui.horizontal(|ui| {
+                ui.label("Theirs: ");
+                ui.text_edit_singleline(&mut nself.merge_theirs);
+                if ui.button("MERGE").clicked() {
+                    nself.libraries_message = Some(match merge_library(nself) {
+                        Ok(conflicts) if conflicts.is_empty() => "merged".to_owned(),
+                        Ok(conflicts) => conflicts
+                            .iter()
+                            .map(|c| format!("{} '{}': {}", c.character, c.combo, c.message))
+                            .collect::<Vec<_>>()
+                            .join("\n"),
+                        Err(e) => e,
+                    });
+                }
+            });
+            if let Some(message) = nself.libraries_message.as_ref() {
+                ui.label(message);
+            }
+        }
+
+        fn preview_wiki(nself: &mut MyApp, text: &str) {
+            let found = wiki::parse(&profile(nself), text);
+            match found {
+                Ok(found) => {
+                    nself.wiki_message = Some(format!("{} combo(s) found", found.len()));
+                    nself.wiki_preview = found.into_iter().map(|f| (true, f)).collect();
+                }
+                Err(e) => {
+                    nself.wiki_message = Some(e);
+                    nself.wiki_preview.clear();
+                }
+            }
+        }
+
+        fn wiki_section(nself: &mut MyApp, ui: &mut egui::Ui) {
+            ui.label("Paste a wiki's combo list, or load a saved page:");
+            ui.text_edit_multiline(&mut nself.wiki_text)
+                .on_hover_text("one combo per line, named after the heading above it");
+            ui.horizontal(|ui| {
+                if ui.button("PREVIEW").clicked() && !nself.wiki_text.is_empty() {
+                    let text = nself.wiki_text.clone();
+                    preview_wiki(nself, &text);
+                }
+                ui.label("File: ");
+                ui.text_edit_singleline(&mut nself.wiki_path)
+                    .on_hover_text("a .html or .txt file");
+                if ui.button("LOAD").clicked() && !nself.wiki_path.is_empty() {
+                    match fs::read_to_string(nself.wiki_path.trim()) {
+                        Ok(text) => preview_wiki(nself, &text),
+                        Err(e) => nself.wiki_message = Some(e.to_string()),
+                    }
+                }
+            });
+            for (add, found) in nself.wiki_preview.iter_mut() {
+                ui.horizontal(|ui| {
+                    ui.checkbox(add, "");
+                    ui.add(egui::TextEdit::singleline(&mut found.name).desired_width(120.0));
+                    ui.label(&found.inputs).on_hover_text(&found.source);
+                    if !found.unknown.is_empty() {
+                        ui.colored_label(
+                            egui::Color32::RED,
+                            format!("unknown {}", found.unknown.join(", ")),
+                        );
+                    }
+                });
+            }
+            if !nself.wiki_preview.is_empty() {
+                ui.horizontal(|ui| {
+                    let target = nself.character_selected.clone();
+                    let label = match target.as_ref() {
+                        Some(character) => format!("ADD TO {}", character.name),
+                        None => "select a character to add to".to_owned(),
+                    };
+                    if ui
+                        .add_enabled(target.is_some(), egui::Button::new(label))
+                        .clicked()
+                    {
+                        if let Some(mut character) = target {
+                            let combos: Vec<Option<Combo>> = nself
+                                .wiki_preview
+                                .iter()
+                                .filter(|(add, _)| *add)
+                                .map(|(_, found)| Some(found.to_combo()))
+                                .collect();
+                            nself.wiki_message = Some(format!("added {} combo(s)", combos.len()));
+                            character.combos.extend(combos);
+                            store_character(nself, character);
+                            nself.wiki_preview.clear();
+                            nself.wiki_text = "".to_owned();
+                        }
+                    }
+                    if ui.button("CLEAR").clicked() {
+                        nself.wiki_preview.clear();
+                        nself.wiki_message = None;
+                    }
+                });
+            }
+            if let Some(message) = nself.wiki_message.as_ref() {
+                ui.label(message);
+            }
+        }
+
+        // edits in the app that a reload from disk would throw away
+        fn unsaved_edits(nself: &MyApp) -> bool {
+            let character = match nself.character_selected.as_ref() {
+                Some(character) => character,
+                None => return !nself.new_inputs.is_empty(),
+            };
+            let stored = nself
+                .character_list
+                .iter()
+                .flatten()
+                .find(|c| c.name == character.name);
+            // the INPUTS box shows the combo uppercase with commas as spaces
+            let shown = |inputs: &str| inputs.replace(',', " ").to_ascii_uppercase();
+            let typed = match character.combos.get(nself.combo_selector as usize) {
+                Some(Some(combo)) if !combo.deleted => shown(&combo.inputs) != shown(&nself.inputs),
+                _ => false,
+            };
+            stored != Some(character) || typed || !nself.new_inputs.is_empty()
         }
-    }
-}
 
-trait RemoveQuotes {
-    fn remove_quotes(self) -> Self;
-}
+        // reads the game file again, keeping the character and combo on screen
+        fn reload_game(nself: &mut MyApp) {
+            let character = nself.character_selected.as_ref().map(|c| c.name.clone());
+            let combo = nself.combo_selector as usize;
+            nself.external_change = None;
+            nself.search_index = None;
+            nself.libraries = None;
+            if let Err(e) = get_character_list(nself) {
+                nself.watch_message = Some(e);
+                return;
+            }
+            if character.is_some() {
+                apply_startup(
+                    nself,
+                    StartupArgs {
+                        character,
+                        combo: Some(combo.to_string()),
+                        ..Default::default()
+                    },
+                );
+            }
+        }
 
-impl RemoveQuotes for String {
-    fn remove_quotes(self) -> Self {
-        self.replace('\"', "")
-    }
-}
-const REGEX_STUFF:  [&str; 7] = ["+", "*", "?", "[", "]", "(", ")"];
-impl eframe::App for MyApp {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        // println!("update!");
-        fn add_combo(c: Character, nself: &mut MyApp, new_combo: Combo) {
-            nself
-                .character_selected
-                .as_mut()
-                .unwrap()
-                .combos
-                .push(Some(new_combo.clone()));
-            let mut i: usize;
-            if c.combos.is_empty() {
-                i = 0;
+        fn file_changed(nself: &mut MyApp, path: PathBuf) {
+            let name = path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            if path == nself.data_dir.join(GAME_LIST) {
+                match fs::read_to_string(&path)
+                    .map_err(|e| e.to_string())
+                    .and_then(|text| serde_json::from_str(&text).map_err(|e| e.to_string()))
+                {
+                    Ok(list) => {
+                        nself.game_list = Some(list);
+                        nself.search_index = None;
+                        nself.watch_message = Some(format!("reloaded {}", name));
+                    }
+                    Err(e) => nself.watch_message = Some(format!("{}: {}", name, e)),
+                }
+                return;
+            }
+            // half-written by an editor or mid git checkout, wait for the next change
+            if let Err(e) = library::load(&path) {
+                nself.watch_message = Some(e);
+                return;
+            }
+            if unsaved_edits(nself) {
+                nself.external_change = Some(path);
             } else {
-                i = c.combos.len();
+                reload_game(nself);
+                nself.watch_message = Some(format!("reloaded {}", name));
             }
-            //let temp_json = serde_json::to_string(&test[2]).unwrap();
-            let mut combos_map = IndexMap::new();
-            for (i, combo) in c.combos.into_iter().enumerate() {
-                combos_map.insert(i.to_string(), combo);
+        }
+
+        // the selected character's combos entered twice, or nearly
+        fn duplicates_section(nself: &mut MyApp, ui: &mut egui::Ui) {
+            let mut character = match (&nself.game_json, nself.character_selected.clone()) {
+                (Some(_), Some(character)) => character,
+                _ => return,
+            };
+            let found = match duplicates::find(&profile(nself), &character, 2) {
+                Ok(found) => found,
+                Err(e) => {
+                    ui.label(e);
+                    return;
+                }
+            };
+            if found.is_empty() {
+                ui.label("No duplicates.");
             }
-            combos_map.insert(i.to_string(), Some(new_combo));
-            if let Some(character) =
-                nself.game_json.as_mut().unwrap()["characters"][c.name].as_object_mut()
-            {
-                if let Some(combos) = character["combos"].as_object_mut() {
-                    // convert indexmap to string, then to a json_literal
-                    let json_literal = serde_json::to_string(&combos_map).unwrap();
-                    let json_literal: Value = serde_json::from_str(json_literal.as_str()).unwrap();
-                    *combos = json_literal.as_object().unwrap().to_owned();
-                }
-            }
-            let formatted_json =
-                serde_json::to_string_pretty(nself.game_json.as_ref().unwrap()).unwrap();
-            //println!("adding {}", formatted_json);
-            fs::write(
-                Path::new(&nself.game_path.to_owned().unwrap()),
-                formatted_json,
-            )
-            .unwrap();
-            let character_list_str =
-                fs::read_to_string(Path::new(nself.game_path.as_ref().unwrap()))
-                    .expect("unable to read input_().json");
-            nself.game_json = serde_json::from_str(&character_list_str).expect("bad input_().json");
-        }
-
-        fn get_character_list(nself: &mut MyApp) {
-            nself.combo_selector = 0.0;
-            nself.read_character_list = false;
-            nself.character_selected = None;
-            if let Some(selected) = nself
-                .game_list
-                .to_owned()
+            let name = |index: usize| {
+                character.combos[index]
+                    .as_ref()
+                    .map(|c| c.name.clone())
+                    .unwrap_or_default()
+            };
+            let mut merge = None;
+            for duplicate in found {
+                ui.horizontal(|ui| {
+                    let apart = match duplicate.distance {
+                        0 => "exact".to_owned(),
+                        n => format!("{} token(s) apart", n),
+                    };
+                    ui.label(format!(
+                        "#{} '{}' and #{} '{}': {}",
+                        duplicate.first,
+                        name(duplicate.first),
+                        duplicate.second,
+                        name(duplicate.second),
+                        apart
+                    ));
+                    if ui
+                        .small_button("MERGE")
+                        .on_hover_text(
+                            "keeps the first, with the second's tags, notes and metadata",
+                        )
+                        .clicked()
+                    {
+                        merge = Some(duplicate);
+                    }
+                });
+            }
+            if let Some(duplicate) = merge {
+                if duplicates::merge_into(&mut character, &duplicate) {
+                    store_character(nself, character);
+                    if nself.combo_selector as usize == duplicate.second {
+                        select_combo(nself, duplicate.first);
+                    }
+                }
+            }
+        }
+
+        // the damage of what's in the INPUTS box, saved combos get theirs
+        // stored by store_character
+        fn update_estimate(nself: &mut MyApp) {
+            nself.estimate = None;
+            let name = match (&nself.game_json, nself.character_selected.as_ref()) {
+                (Some(_), Some(character)) => character.name.clone(),
+                _ => return,
+            };
+            match damage::for_inputs(&profile(nself), &name, &nself.inputs) {
+                Ok(estimate) => nself.estimate = estimate,
+                Err(e) => nself.export_message = Some(e),
+            }
+        }
+
+        fn search_section(nself: &mut MyApp, ui: &mut egui::Ui) {
+            ui.horizontal(|ui| {
+                ui.text_edit_singleline(&mut nself.search_query)
+                    .on_hover_text("e.g. starts:2LK and motion:236 length < 8, see query.rs");
+                if ui.button("REFRESH").clicked() {
+                    nself.search_index = None;
+                }
+            });
+            let mut saved_changed = false;
+            ui.horizontal(|ui| {
+                egui::ComboBox::from_id_source("saved_queries")
+                    .selected_text("Saved")
+                    .show_ui(ui, |ui| {
+                        for (name, query) in nself.settings.saved_queries.iter() {
+                            if ui.selectable_label(false, name).clicked() {
+                                nself.search_query = query.clone();
+                                nself.query_name = name.clone();
+                            }
+                        }
+                    });
+                ui.text_edit_singleline(&mut nself.query_name);
+                let name = nself.query_name.trim().to_owned();
+                if ui.button("SAVE").clicked() && !name.is_empty() {
+                    let query = nself.search_query.clone();
+                    nself.settings.saved_queries.insert(name.clone(), query);
+                    saved_changed = true;
+                }
+                if ui.button("DELETE").clicked()
+                    && nself.settings.saved_queries.shift_remove(&name).is_some()
+                {
+                    saved_changed = true;
+                }
+            });
+            if saved_changed {
+                let path = nself.data_dir.join(settings::SETTINGS);
+                nself.search_message = nself.settings.save(&path).err();
+            }
+            if nself.search_query.trim().is_empty() {
+                return;
+            }
+            if nself.search_index.is_none() {
+                let game_list = nself.game_list.clone().unwrap_or_default();
+                let (index, errors) = search::Index::build(
+                    &nself.data_dir,
+                    &game_list,
+                    &nself.default_json,
+                    &nself.icon_packs,
+                );
+                nself.search_message = (!errors.is_empty()).then(|| errors.join("\n"));
+                nself.search_index = Some(index);
+            }
+            let mut jump = None;
+            let hits = match nself
+                .search_index
+                .as_ref()
                 .unwrap()
-                .get(nself.game_selected.to_owned().unwrap())
+                .search(&nself.search_query)
             {
-                nself.character_list.as_mut().unwrap().clear();
-                let selected = selected.to_string().remove_quotes();
-                nself.game_path = Some(format!("src\\games\\input_{}.json", selected));
-                let character_list_str =
-                    fs::read_to_string(Path::new(nself.game_path.as_ref().unwrap()))
-                        .expect("unable to read input_().json");
-                nself.game_json =
-                    serde_json::from_str(&character_list_str).expect("bad input_().json");
-                let characters = nself.game_json.as_ref().unwrap().get("characters").unwrap();
-                match characters {
-                    Value::Object(obj) => {
-                        for (k, v) in obj.iter() {
-                            let temp: Value = serde_json::from_value(v.clone()).unwrap();
-                            let temp: Value = temp.get("combos").unwrap().to_owned();
-                            let mut temp_combos: Vec<Option<Combo>> = Vec::new();
-                            match temp {
-                                Value::Object(combo_obj) => {
-                                    for (combo_k, combo_v) in combo_obj {
-                                        let combo: Option<Combo> =
-                                            serde_json::from_value(combo_v).unwrap();
-                                        temp_combos.push(combo);
-                                    }
-                                }
-                                _ => panic!(),
-                            }
-                            // println!("{} - {:?}\n", k, v);
-                            let temp_character: Character =
-                                Character::new(k.to_string(), temp_combos);
-                            nself.character_list.as_mut().unwrap().push(temp_character);
+                Ok(hits) => hits,
+                Err(e) => {
+                    ui.label(e);
+                    return;
+                }
+            };
+            ui.label(format!("{} combo(s)", hits.len()));
+            egui::ScrollArea::vertical()
+                .id_source("search_results")
+                .max_height(150.0)
+                .show(ui, |ui| {
+                    for hit in hits {
+                        let text = format!(
+                            "{} / {} / {}: {}",
+                            hit.game,
+                            hit.character,
+                            hit.combo.name,
+                            hit.combo.inputs.replace('\n', " ")
+                        );
+                        if ui.selectable_label(false, text).clicked() {
+                            jump = Some(StartupArgs {
+                                game: Some(hit.game_id.clone()),
+                                character: Some(hit.character.clone()),
+                                combo: Some(hit.index.to_string()),
+                                ..Default::default()
+                            });
                         }
                     }
-                    _ => {
-                        panic!("invalid json");
-                    }
-                };
-                // println!("game list - {:?}", nself.game_list);
-            }
-        }
-        fn compare_combinations(
-            combinations: &mut String,
-            attacks: &mut [String],
-        ) -> Option<Vec<String>> {
-            for a in attacks.iter_mut() {
-                //println!("274: {}", a);
-                if *a == "+" || *a == "*" || *a == "?"  || *a == "[" || *a == "]" {
-                    *a = format!("\\{}", a);
-                }
-
-            }
-            // *combinations = combinations.replace('(', "\\(");
-            // *combinations = combinations.replace(')', "\\)");
-
-            let attacks_re = attacks.join("|").to_ascii_uppercase();
-            let re = Regex::new(&attacks_re).unwrap();
-            // println!("285: {:?}", re);
-            let mut temp_inputs: Vec<String> = Vec::new();
-            let mut mapped_inputs: Vec<Vec<String>> = Vec::new();
-            let mut temp_combination = combinations.to_string();
-            while temp_inputs.join("").len() < combinations.len() {
-                while let Some(m) = re.find(temp_combination.clone().as_str()) {
-                    let temp_temp = temp_combination.clone();
-                    let mut tokens: Vec<&str> = temp_temp.splitn(2, m.as_str()).collect();
-                    // println!("293: {:?}", tokens);
-                    if !tokens[0].is_empty() {
-                        temp_combination.remove(0);
-                        temp_inputs.push("0".to_string());
-                    } else {
-                        let mut token = m.as_str().to_owned();
-                        for c in REGEX_STUFF {
-                            token = token.replace(c, format!("\\{}", c).as_str());
+                });
+            if let Some(message) = nself.search_message.as_ref() {
+                ui.label(message);
+            }
+            if let Some(args) = jump {
+                apply_startup(nself, args);
+            }
+        }
 
+        // notes and tags of the selected combo, saved with SAVE NOTES
+        fn notes_section(nself: &mut MyApp, ui: &mut egui::Ui) {
+            let index = nself.combo_selector as usize;
+            let mut character = match nself.character_selected.clone() {
+                Some(character) => character,
+                None => return,
+            };
+            let combo = match character.combos.get_mut(index) {
+                Some(Some(combo)) if !combo.deleted => combo,
+                _ => return,
+            };
+            let notes_label = ui.label("Notes: ");
+            ui.text_edit_multiline(&mut combo.notes)
+                .labelled_by(notes_label.id);
+            let mut tags = combo.tags.join(", ");
+            ui.horizontal(|ui| {
+                let tags_label = ui.label("Tags: ");
+                if ui
+                    .text_edit_singleline(&mut tags)
+                    .labelled_by(tags_label.id)
+                    .changed()
+                {
+                    combo.tags = tags
+                        .split(',')
+                        .map(|t| t.trim().to_owned())
+                        .filter(|t| !t.is_empty())
+                        .collect();
+                }
+            });
+            let save = ui.button("SAVE NOTES").clicked();
+            nself.character_selected = Some(character.clone());
+            if save {
+                let mapped = std::mem::take(&mut nself.mapped_inputs);
+                store_character(nself, character);
+                nself.mapped_inputs = mapped;
+                nself.changed_inputs = false;
+            }
+        }
+
+        fn help_window(nself: &mut MyApp, ctx: &egui::Context) {
+            let mut open = nself.show_help;
+            egui::Window::new("SHORTCUTS")
+                .open(&mut open)
+                .collapsible(false)
+                .auto_sized()
+                .show(ctx, |ui| {
+                    egui::Grid::new("shortcuts_help").show(ui, |ui| {
+                        for action in Action::ALL {
+                            ui.label(action.label());
+                            ui.monospace(shortcut_text(nself, action));
+                            ui.end_row();
                         }
-                        temp_combination = tokens[1].to_owned();
-                        temp_inputs.push(token);
-                    }
+                    });
+                    ui.label("Change them in SETTINGS.");
+                });
+            nself.show_help = open;
+        }
+
+        // Reads the selected game's file. Nothing changes when it can't be
+        // read, so a bad edit on disk leaves the game on screen as it was.
+        fn get_character_list(nself: &mut MyApp) -> Result<(), String> {
+            let selected = match nself
+                .game_list
+                .as_ref()
+                .zip(nself.game_selected.as_ref())
+                .and_then(|(list, game)| list.get(game))
+            {
+                Some(selected) => selected.to_string().remove_quotes(),
+                None => return Ok(()),
+            };
+            let game_path = nself.data_dir.join(format!("input_{}.json", selected));
+            let text = fs::read_to_string(&game_path)
+                .map_err(|e| format!("unable to read {}: {}", game_path.display(), e))?;
+            let mut game: Value = serde_json::from_str(&text)
+                .map_err(|e| format!("bad json in {}: {}", game_path.display(), e))?;
+            let report = schema::migrate(&mut game)
+                .map_err(|e| format!("{}: {}", game_path.display(), e))?;
+            let characters = combo::characters_from_json(&game)
+                .map_err(|e| format!("{}: {}", game_path.display(), e))?;
+            // the upgraded layout is written with the next change, not just
+            // for opening the game
+            if report.changed() {
+                nself.watch_message = Some(format!(
+                    "{} is schema v{}, it's saved as v{} with the next change",
+                    selected, report.from, report.to
+                ));
+            }
+            nself.combo_selector = 0.0;
+            nself.read_character_list = false;
+            nself.character_selected = None;
+            let mut resolved = false;
+            for character in characters.iter() {
+                resolved |= nself.collections.resolve(&selected, character);
+            }
+            if resolved {
+                save_collections(nself);
+            }
+            nself.character_list = Some(characters);
+            nself.game_json = Some(game);
+            if let Some(old) = nself.game_path.as_ref() {
+                nself.watcher.unwatch(Path::new(old));
+            }
+            nself.watcher.watch(&game_path);
+            nself.game_path = Some(game_path.to_string_lossy().to_string());
+            let stats_path = stats::path(&nself.data_dir, &selected);
+            match Stats::load(&stats_path) {
+                Ok(stats) => {
+                    nself.stats = stats;
+                    nself.stats_path = Some(stats_path);
                 }
-                if temp_inputs.join("").len() < combinations.len() {
-                    temp_inputs.push("0".to_string());
+                Err(e) => {
+                    nself.stats = Stats::default();
+                    nself.stats_path = None;
+                    nself.practice_message = Some(e);
                 }
             }
-            mapped_inputs.push(temp_inputs.clone());
-            Some(temp_inputs)
+            Ok(())
+        }
+        settings_window(self, ctx, frame);
+        if self.apply_settings {
+            self.apply_settings = false;
+            apply_settings(self, ctx, frame);
         }
 
-        egui::CentralPanel::default().show(ctx, |ui| {
-            egui::CollapsingHeader::new("GAME OPTIONS")
-                .default_open(true)
-                .show(ui, |ui| {
-                    if self.read_game_list {
-                        self.read_game_list = false;
-                        let games_list_str = fs::read_to_string(Path::new(GAME_LIST))
-                            .expect("Unable to read game_list_name.json");
-                        self.game_list = serde_json::from_str(&games_list_str).expect("bad json.");
-                        if let Some(selected) =
-                            self.game_list.to_owned().unwrap().get("previous_choice")
-                        {
-                            if selected != "null" {
-                                self.game_selected = Some(selected.to_string().remove_quotes());
-                            };
-                        }
-                        get_character_list(self);
-                    }
-                    if self.get_images {
-                        self.get_images = false;
-                        self.retained_images.clear();
-                        for (_k, v) in self.game_json.as_ref().unwrap()["attacks"]
-                            .as_object()
-                            .unwrap()
-                            .iter()
-                        {
-                            if v != "skip" {
-                                let mut buffer = vec![];
-                                let v = v.to_string().remove_quotes();
-                                let path = format!("images\\{}", v);
-                                File::open(&path).unwrap().read_to_end(&mut buffer).unwrap();
-                                let retained = RetainedImage::from_image_bytes(v, &buffer).unwrap();
-                                self.retained_images.push(Some(retained));
+        // dropped .md/.csv/.txt files are imported, folders and zips are
+        // installed as icon packs
 
-                            }
-                        }
-                        for (_k, v) in self.default_json["movement"].as_object().unwrap().iter() {
-                            if v != "skip" {
-                                let mut buffer = vec![];
-                                let v = v.to_string().remove_quotes();
-                                let path = format!("images\\{}", v);
-                                File::open(&path).unwrap().read_to_end(&mut buffer).unwrap();
-                                let retained = RetainedImage::from_image_bytes(v, &buffer).unwrap();
-                                self.retained_images.push(Some(retained));
+        for file in ctx.input(|i| i.raw.dropped_files.clone()) {
+            if let Some(path) = file.path {
+                let format = path
+                    .extension()
+                    .and_then(|ext| Format::from_extension(&ext.to_string_lossy()));
+                match format {
+                    Some(format) if self.game_json.is_some() => match fs::read_to_string(&path) {
+                        Ok(text) => import_library(self, &text, format),
+                        Err(e) => self.library_message = Some(e.to_string()),
+                    },
+                    _ => install_icon_pack(self, &path),
+                }
+            }
+        }
 
-                            }
-                        }
+        let window = frame.info().window_info;
+        self.window_pos = window.position;
+        self.window_size = Some(window.size);
+
+        if self.read_game_list {
+            self.read_game_list = false;
+            self.watcher.watch(&self.data_dir.join(GAME_LIST));
+            let games_list_str = fs::read_to_string(self.data_dir.join(GAME_LIST))
+                .expect("Unable to read game_list_name.json");
+            self.game_list = serde_json::from_str(&games_list_str).expect("bad json.");
+            // older versions kept the last game in the list itself
+            if let Some(selected) = self.game_list.to_owned().unwrap().get("previous_choice") {
+                if selected != "null" {
+                    self.game_selected = Some(selected.to_string().remove_quotes());
+                };
+            }
+            if self.game_selected.is_some() {
+                switch_game(self, None);
+            }
+        }
+        if let Some(args) = self.startup.take() {
+            apply_startup(self, args);
+        }
+        for path in self.watcher.poll(ctx.input(|i| i.time)) {
+            file_changed(self, path);
+        }
+        ctx.request_repaint_after(Duration::from_secs_f64(self.watcher.interval));
+        // waits for a game, the tokens come from its profile
+        if self.get_images && self.game_json.is_some() {
+            self.get_images = false;
+            self.retained_images.clear();
+            for token in known_tokens(self) {
+                if let Some((path, _scale)) = icon_path(self, &token) {
+                    if self
+                        .retained_images
+                        .iter()
+                        .flatten()
+                        .any(|x| x.debug_name() == path)
+                    {
+                        continue;
+                    }
+                    // a missing or broken image is left out, not worth a crash
+                    let mut buffer = vec![];
+                    let loaded = File::open(&path)
+                        .and_then(|mut file| file.read_to_end(&mut buffer))
+                        .map_err(|e| e.to_string())
+                        .and_then(|_| RetainedImage::from_image_bytes(&path, &buffer));
+                    match loaded {
+                        Ok(retained) => self.retained_images.push(Some(retained)),
+                        Err(e) => eprintln!("skipping image {}: {}", path, e),
                     }
+                }
+            }
+        }
+
+        // the recorder needs letter keys, shortcuts wait until it stops
+        if self.recording.is_some() {
+            record_keys(self, ctx);
+        } else {
+            handle_shortcuts(self, ctx);
+        }
+        // practice stops when the combo changes under it
+        if matches!(self.practice.as_ref(), Some(p) if p.inputs != self.inputs) {
+            self.practice = None;
+        }
+        if matches!(self.practice.as_ref(), Some(p) if p.interval.is_some()) {
+            practice_step(self, Some(ctx.input(|i| i.stable_dt)));
+            ctx.request_repaint();
+        }
+        help_window(self, ctx);
+        if self.decorated == self.compact {
+            self.decorated = !self.compact;
+            frame.set_decorations(self.decorated);
+        }
+        if self.compact {
+            hud(self, ctx, frame);
+            return;
+        }
 
+        egui::CentralPanel::default().show(ctx, |ui| {
+            if let Some(path) = self.external_change.clone() {
+                ui.colored_label(
+                    egui::Color32::YELLOW,
+                    format!(
+                        "{} changed on disk while you have unsaved edits.",
+                        path.display()
+                    ),
+                );
+                ui.horizontal(|ui| {
+                    if ui
+                        .button("RELOAD")
+                        .on_hover_text("take the file, drop the edits here")
+                        .clicked()
+                    {
+                        reload_game(self);
+                    }
+                    if ui
+                        .button("KEEP MINE")
+                        .on_hover_text("the next save writes over the file")
+                        .clicked()
+                    {
+                        self.external_change = None;
+                    }
+                });
+            }
+            if let Some(message) = self.watch_message.as_ref() {
+                ui.label(message);
+            }
+            egui::CollapsingHeader::new("GAME OPTIONS")
+                .default_open(true)
+                .show(ui, |ui| {
                     // ui.label("Contents");
                     ui.horizontal(|ui| {
                         ui.vertical(|ui| {
@@ -370,25 +2468,7 @@ impl eframe::App for MyApp {
                                             self.game_selected != temp_selection;
                                     }
                                     if self.read_character_list {
-                                        // save game choice to json
-                                        self.get_images = true;
-                                        self.changed_inputs = true;
-                                        if let Some(choosen_game) = self
-                                            .game_list
-                                            .as_mut()
-                                            .unwrap()
-                                            .get_mut("previous_choice")
-                                        {
-                                            *choosen_game = Value::String(
-                                                self.game_selected.to_owned().unwrap(),
-                                            );
-                                        }
-                                        std::fs::write(
-                                            Path::new(GAME_LIST),
-                                            serde_json::to_string_pretty(&self.game_list).unwrap(),
-                                        )
-                                        .unwrap();
-                                        get_character_list(self);
+                                        switch_game(self, temp_selection);
                                     }
                                 });
 
@@ -402,20 +2482,17 @@ impl eframe::App for MyApp {
                                     ui.end_row();
                                     ui.horizontal(|ui| {
                                         egui::ComboBox::from_id_source("character_box")
-                                            .selected_text(if self.character_selected.is_some() {
+                                            .selected_text(
                                                 self.character_selected
                                                     .as_ref()
-                                                    .unwrap()
-                                                    .name
-                                                    .clone()
-                                            } else {
-                                                "Select a character".to_string()
-                                            })
+                                                    .map(|c| c.name.clone())
+                                                    .unwrap_or_else(|| {
+                                                        "Select a character".to_string()
+                                                    }),
+                                            )
                                             .show_ui(ui, |ui| {
-                                                if self.character_list.is_none() {
-                                                    get_character_list(self);
-                                                } else {
-                                                    for c in self.character_list.as_ref().unwrap() {
+                                                if let Some(list) = &self.character_list {
+                                                    for c in list {
                                                         let c_name =
                                                             c.name.to_owned().remove_quotes();
                                                         if ui
@@ -426,11 +2503,12 @@ impl eframe::App for MyApp {
                                                             )
                                                             .changed()
                                                         {
-                                                            // println!("changed char");
                                                             self.changed_inputs = true;
                                                             self.mapped_inputs.clear();
                                                         };
                                                     }
+                                                } else if let Err(e) = get_character_list(self) {
+                                                    self.watch_message = Some(e);
                                                 }
                                                 if let Some(selected) =
                                                     self.character_selected.as_ref()
@@ -439,22 +2517,11 @@ impl eframe::App for MyApp {
                                                         != self.previous_choice
                                                     {
                                                         self.combo_selector = 0.0;
-                                                        if !self
-                                                            .character_selected
-                                                            .as_ref()
-                                                            .unwrap()
-                                                            .combos
-                                                            .is_empty()
+                                                        if let Some((index, combo)) =
+                                                            selected.live().next()
                                                         {
-                                                            self.inputs = self
-                                                                .character_selected
-                                                                .as_ref()
-                                                                .unwrap()
-                                                                .combos
-                                                                [self.combo_selector as usize]
-                                                                .to_owned()
-                                                                .unwrap()
-                                                                .inputs;
+                                                            self.combo_selector = index as f32;
+                                                            self.inputs = combo.inputs.clone();
                                                         }
                                                     }
                                                 }
@@ -465,123 +2532,224 @@ impl eframe::App for MyApp {
                         });
 
                         //ui.add_space(70.00);
-                        egui::CollapsingHeader::new("ADD").show(ui, |ui| {
-                            if ui.button("ADD COMBO").clicked() {
-                                self.show_window = !self.show_window;
-                            }
-                            if self.show_window {
-                                egui::Window::new("ADD NEW COMBO")
-                                    .collapsible(false)
-                                    .min_width(WIDTH + 20.0)
-                                    .auto_sized()
-                                    .fixed_pos(Pos2::new(0.0, 0.0))
-                                    .show(ctx, |ui| {
-                                        ui.label(
-                                            self.game_selected
-                                                .as_ref()
-                                                .unwrap_or(&"what".to_string()),
-                                        );
-                                        ui.horizontal(|ui| {
-                                            egui::ComboBox::from_label("")
-                                                .selected_text(
-                                                    if self.character_selected.is_some() {
+                        if ui.button("SETTINGS").clicked() {
+                            self.show_settings = !self.show_settings;
+                        }
+                        let open = std::mem::take(&mut self.open_add).then_some(true);
+                        egui::CollapsingHeader::new("ADD")
+                            .open(open)
+                            .show(ui, |ui| {
+                                if ui.button("ADD COMBO").clicked() {
+                                    self.show_window = !self.show_window;
+                                }
+                                if self.show_window {
+                                    egui::Window::new("ADD NEW COMBO")
+                                        .collapsible(false)
+                                        .min_width(WIDTH + 20.0)
+                                        .auto_sized()
+                                        .fixed_pos(Pos2::new(0.0, 0.0))
+                                        .show(ctx, |ui| {
+                                            ui.label(
+                                                self.game_selected
+                                                    .as_ref()
+                                                    .unwrap_or(&"what".to_string()),
+                                            );
+                                            ui.horizontal(|ui| {
+                                                egui::ComboBox::from_label("")
+                                                    .selected_text(
                                                         self.character_selected
                                                             .as_ref()
-                                                            .unwrap()
-                                                            .name
-                                                            .clone()
-                                                    } else {
-                                                        "Select a character".to_string()
-                                                    },
-                                                )
-                                                .show_ui(ui, |ui| {
-                                                    if self.character_list.is_none() {
-                                                        get_character_list(self);
-                                                    } else {
-                                                        for c in
-                                                            self.character_list.as_ref().unwrap()
+                                                            .map(|c| c.name.clone())
+                                                            .unwrap_or_else(|| {
+                                                                "Select a character".to_string()
+                                                            }),
+                                                    )
+                                                    .show_ui(ui, |ui| {
+                                                        if let Some(list) = &self.character_list {
+                                                            for c in list {
+                                                                let c_name = c
+                                                                    .name
+                                                                    .to_owned()
+                                                                    .remove_quotes();
+                                                                ui.selectable_value(
+                                                                    &mut self.character_selected,
+                                                                    Some(c.to_owned()),
+                                                                    c_name,
+                                                                );
+                                                            }
+                                                        } else if let Err(e) =
+                                                            get_character_list(self)
                                                         {
-                                                            let c_name =
-                                                                c.name.to_owned().remove_quotes();
-                                                            ui.selectable_value(
-                                                                &mut self.character_selected,
-                                                                Some(c.to_owned()),
-                                                                c_name,
-                                                            );
+                                                            self.watch_message = Some(e);
                                                         }
-                                                    }
-                                                })
-                                        });
-                                        ui.horizontal_wrapped(|ui| {
-                                            let game_name = ui.label("Name: ");
-                                            ui.text_edit_singleline(&mut self.description)
-                                                .labelled_by(game_name.id);
-                                        });
-                                        ui.horizontal_wrapped(|ui| {
-                                            let inputs = ui.label("Inputs: ");
-                                            ui.text_edit_multiline(&mut self.new_inputs)
-                                                .labelled_by(inputs.id);
-                                        });
+                                                    })
+                                            });
+                                            ui.horizontal_wrapped(|ui| {
+                                                let game_name = ui.label("Name: ");
+                                                ui.text_edit_singleline(&mut self.description)
+                                                    .labelled_by(game_name.id);
+                                            });
+                                            ui.horizontal_wrapped(|ui| {
+                                                let inputs = ui.label("Inputs: ");
+                                                ui.text_edit_multiline(&mut self.new_inputs)
+                                                    .labelled_by(inputs.id);
+                                            });
 
-                                        ui.add_space(10.0);
-                                        ui.horizontal_wrapped(|ui| {
-                                            if ui.button("ADD TEST").clicked() {
-                                                let temp_combo = Combo::new(
-                                                    self.description.to_owned(),
-                                                    self.new_inputs.to_ascii_uppercase(),
-                                                    ComboState::Testing,
-                                                );
-                                                add_combo(
-                                                    self.character_selected.to_owned().unwrap(),
-                                                    self,
-                                                    temp_combo,
-                                                );
-                                                self.new_inputs = "".to_owned();
-                                                self.description = "".to_owned();
-                                                self.show_window = false;
-                                            };
-                                            if ui.button("CANCEL").clicked() {
-                                                self.new_inputs = "".to_owned();
-                                                self.description = "".to_owned();
-                                                self.show_window = false;
-                                            };
+                                            ui.add_space(10.0);
+                                            ui.horizontal_wrapped(|ui| {
+                                                if ui.button("ADD TEST").clicked() {
+                                                    let temp_combo = Combo::new(
+                                                        self.description.to_owned(),
+                                                        self.new_inputs.to_ascii_uppercase(),
+                                                        ComboState::Testing,
+                                                    );
+                                                    add_combo(
+                                                        self.character_selected.to_owned().unwrap(),
+                                                        self,
+                                                        temp_combo,
+                                                    );
+                                                    self.new_inputs = "".to_owned();
+                                                    self.description = "".to_owned();
+                                                    self.show_window = false;
+                                                };
+                                                if ui.button("CANCEL").clicked() {
+                                                    self.new_inputs = "".to_owned();
+                                                    self.description = "".to_owned();
+                                                    self.show_window = false;
+                                                };
+                                            });
                                         });
-                                    });
+                                }
+                            });
+                    });
+                    if self.game_json.is_some() {
+                        egui::CollapsingHeader::new("LIBRARIES").show(ui, |ui| {
+                            libraries_section(self, ui);
+                        });
+                        egui::CollapsingHeader::new("ICON PACKS").show(ui, |ui| {
+                            let mut chain = icon_chain(self);
+                            let mut changed = false;
+                            // enabled packs in priority order, then the rest
+                            let mut packs: Vec<&IconPack> = chain
+                                .iter()
+                                .filter_map(|id| self.icon_packs.iter().find(|p| &p.id == id))
+                                .collect();
+                            packs.extend(self.icon_packs.iter().filter(|p| !chain.contains(&p.id)));
+                            for pack in packs {
+                                ui.horizontal(|ui| {
+                                    let position = chain.iter().position(|id| id == &pack.id);
+                                    let mut enabled = position.is_some();
+                                    let credits = if pack.manifest.credits.is_empty() {
+                                        pack.id.clone()
+                                    } else {
+                                        pack.manifest.credits.clone()
+                                    };
+                                    if ui
+                                        .checkbox(&mut enabled, &pack.manifest.name)
+                                        .on_hover_text(credits)
+                                        .changed()
+                                    {
+                                        if enabled {
+                                            chain.push(pack.id.clone());
+                                        } else {
+                                            chain.retain(|id| id != &pack.id);
+                                        }
+                                        changed = true;
+                                    }
+                                    if let Some(i) = position {
+                                        if ui.small_button("⬆").clicked() && i > 0 {
+                                            chain.swap(i, i - 1);
+                                            changed = true;
+                                        }
+                                        if ui.small_button("⬇").clicked() && i + 1 < chain.len() {
+                                            chain.swap(i, i + 1);
+                                            changed = true;
+                                        }
+                                    }
+                                });
+                            }
+                            if changed {
+                                set_icon_chain(self, chain);
+                            }
+                            ui.horizontal(|ui| {
+                                let pack_path = ui.label("Install: ");
+                                ui.text_edit_singleline(&mut self.new_pack_path)
+                                    .labelled_by(pack_path.id)
+                                    .on_hover_text(
+                                        "a pack folder or zip, or drop one on the window",
+                                    );
+                                if ui.button("INSTALL").clicked() && !self.new_pack_path.is_empty()
+                                {
+                                    let source = self.new_pack_path.trim().to_owned();
+                                    install_icon_pack(self, Path::new(&source));
+                                    self.new_pack_path = "".to_owned();
+                                }
+                            });
+                            if let Some(message) = self.pack_message.as_ref() {
+                                ui.label(message);
                             }
                         });
-                    });
+                        egui::CollapsingHeader::new("WIKI IMPORT").show(ui, |ui| {
+                            wiki_section(self, ui);
+                        });
+                        egui::CollapsingHeader::new("EXPORT / IMPORT").show(ui, |ui| {
+                            ui.horizontal(|ui| {
+                                for format in Format::ALL {
+                                    ui.selectable_value(
+                                        &mut self.library_format,
+                                        format,
+                                        format.name(),
+                                    );
+                                }
+                            });
+                            ui.horizontal_wrapped(|ui| {
+                                if let Some(character) = self.character_selected.clone() {
+                                    if ui.button("Export character").clicked() {
+                                        let name = format!(
+                                            "{}_{}",
+                                            self.game_selected.clone().unwrap_or_default(),
+                                            character.name
+                                        );
+                                        export_library(self, vec![character.clone()], &name);
+                                    }
+                                    if ui.button("Copy character").clicked() {
+                                        let text =
+                                            formats::export(&[character], self.library_format);
+                                        ui.output_mut(|o| o.copied_text = text);
+                                        self.library_message = Some("copied".to_owned());
+                                    }
+                                }
+                                if ui.button("Export game").clicked() {
+                                    let characters =
+                                        self.character_list.clone().unwrap_or_default();
+                                    let name = self.game_selected.clone().unwrap_or_default();
+                                    export_library(self, characters, &name);
+                                }
+                            });
+                            ui.label("Paste combos (or drop a .md/.csv/.txt file):");
+                            ui.text_edit_multiline(&mut self.library_text);
+                            if ui.button("IMPORT").clicked() && !self.library_text.is_empty() {
+                                let text = std::mem::take(&mut self.library_text);
+                                import_library(self, &text, self.library_format);
+                            }
+                            ui.horizontal(|ui| {
+                                ui.text_edit_singleline(&mut self.share_code);
+                                if ui.button("Paste share code").clicked() {
+                                    let code = std::mem::take(&mut self.share_code);
+                                    paste_share_code(self, &code);
+                                }
+                            });
+                            if let Some(message) = self.library_message.as_ref() {
+                                ui.label(message);
+                            }
+                        });
+                    }
                 });
+            egui::CollapsingHeader::new("SEARCH").show(ui, |ui| {
+                search_section(self, ui);
+            });
             egui::ScrollArea::vertical().show(ui, |ui| {
-                if self.character_selected.is_some()
-                    && !self.character_selected.as_ref().unwrap().combos.is_empty()
-                {
-                    let slider_size: f32 =
-                        self.character_selected.as_ref().unwrap().combos.len() as f32 - 1.0;
-                    if slider_size < self.combo_selector {
-                        self.combo_selector = slider_size
-                    };
-                    let selected_combos = self.character_selected.as_ref().unwrap().combos
-                        [self.combo_selector as usize]
-                        .to_owned()
-                        .unwrap();
-                    if ui
-                        .add(
-                            egui::Slider::new(&mut self.combo_selector, 0.0..=slider_size)
-                                .step_by(1.0)
-                                .fixed_decimals(0)
-                                .text(selected_combos.name),
-                        )
-                        .changed()
-                    {
-                        self.changed_inputs = true;
-                        self.mapped_inputs.clear();
-                        self.inputs = self.character_selected.as_ref().unwrap().combos
-                            [self.combo_selector as usize]
-                            .clone()
-                            .unwrap()
-                            .inputs;
-                    };
-                }
+                combo_slider(self, ui);
                 egui::CollapsingHeader::new("INPUTS").show(ui, |ui| {
                     let name_label = ui.label("Inputs: ");
                     if ui
@@ -593,81 +2761,79 @@ impl eframe::App for MyApp {
                         self.changed_inputs = true;
                         self.mapped_inputs.clear();
                     };
+                    notes_section(self, ui);
                 });
-                if ui.button("Toggle").clicked() {
-                    self.show_images = !self.show_images;
-                }
-                if self.show_images && !self.inputs.is_empty() && self.changed_inputs {
-                    self.changed_inputs = false;
-                    self.inputs = str::replace(&self.inputs, ',', " ");
-                    let mut translate: Vec<String> = Vec::new();
-                    for attack in self.game_json.as_ref().unwrap()["attacks"]
-                        .as_object()
-                        .unwrap()
-                        .iter()
+                ui.horizontal(|ui| {
+                    if ui.button("Toggle").clicked() {
+                        self.show_images = !self.show_images;
+                    }
+                    let key = shortcut_text(self, Action::ToggleHud);
+                    if ui.button("HUD").on_hover_text(key).clicked() {
+                        self.compact = true;
+                    }
+                    ui.selectable_value(&mut self.render_mode, RenderMode::Icons, "Icons");
+                    ui.selectable_value(&mut self.render_mode, RenderMode::Text, "Text");
+                    if ui.button("Export image").clicked() && self.game_json.is_some() {
+                        export_image(self);
+                    }
+                    if ui.button("Copy share code").clicked() {
+                        copy_share_code(self, ctx);
+                    }
+                    if ui
+                        .selectable_label(self.practice.is_some(), "Practice")
+                        .clicked()
                     {
-                        translate.push(attack.0.to_owned());
-                    }
-                    for movement in self.default_json["movement"].as_object().unwrap().iter() {
-                        translate.push(movement.0.to_owned());
-                    }
-                    // println!("{:?}", translate);
-                    //println!("{}", self.json["attacks"]);
-                    let inputs = self.inputs.split('\n');
-                    for combinations in inputs {
-                        let mut combinations: String = combinations.to_owned();
-                        self.mapped_inputs
-                            .push(compare_combinations(&mut combinations, &mut translate));
-                    }
-                } else if !self.show_images {
-                    self.changed_inputs = true;
-                    self.mapped_inputs.clear();
-                }
-                // println!("{:?}", self.mapped_inputs);
-                for translation in self.mapped_inputs.iter_mut().flatten() {
-                    ui.horizontal_wrapped(|ui| {
-                        for input in translation.iter_mut() {
-                            // println!("651: {}", input);
-                            if let Some(m) = self.game_json.as_ref().unwrap()["attacks"]
-                                .as_object()
-                                .unwrap()
-                                .get(input)
-                                .or(self.default_json["movement"]
-                                    .as_object()
-                                    .unwrap()
-                                    .get(input))
-                            {
-                                // find the index that has m in self.retained_images
-                                let idx = self.retained_images.iter().position(|x| {
-                                    // println!("{} - {}", x.as_ref().unwrap().debug_name(), m.to_string().remove_quotes());
-                                    // println!("{} - {}", x.as_ref().unwrap().debug_name(), m.to_string().remove_quotes());
-                                    x.as_ref().unwrap().debug_name()
-                                        == m.to_string().remove_quotes()
-                                });
-                                if let Some(idx) = idx {
-                                    self.retained_images[idx].as_ref().unwrap().show(ui);
-                                }
-                            } else {
-                                // println!("{}", self.json_default["movement"]["_"]);
-                                if let Some(m) = self.default_json["movement"].get("_") {
-                                    let idx = self.retained_images.iter().position(|x| {
-                                        // println!("{} - {}", x.as_ref().unwrap().debug_name(), "default\\\\err.png".to_string().remove_quotes());
-                                        x.as_ref().unwrap().debug_name().trim()
-                                            == m.to_string().remove_quotes()
-                                    });
-                                    if let Some(idx) = idx {
-                                        self.retained_images[idx].as_ref().unwrap().show(ui);
-                                    }
-                                }
-                            }
+                        toggle_practice(self);
+                    }
+                    if let Some(current) = current_ref(self) {
+                        let star = if self.collections.is_favourite(&current) {
+                            "★"
+                        } else {
+                            "☆"
+                        };
+                        if ui.button(star).on_hover_text("favourite").clicked() {
+                            self.collections.toggle_favourite(current);
+                            save_collections(self);
+                        }
+                    }
+                });
+                if let Some(practice) = self.practice.as_ref() {
+                    let status = practice.status();
+                    ui.horizontal(|ui| {
+                        ui.label(status);
+                        if ui.button("Next").clicked() {
+                            practice_step(self, None);
+                        }
+                        if ui.button("Landed").clicked() {
+                            practice_land(self);
+                        }
+                        if ui.button("Miss").clicked() {
+                            practice_miss(self);
                         }
-                        // println!("__");
-                        // add egui separator
-                    });
-                    ui.vertical(|ui| {
-                        ui.separator();
                     });
                 }
+                if let Some(message) = self.practice_message.as_ref() {
+                    ui.label(message);
+                }
+                egui::CollapsingHeader::new("LINKS").show(ui, |ui| {
+                    links_section(self, ui);
+                });
+                egui::CollapsingHeader::new("COLLECTIONS").show(ui, |ui| {
+                    collections_section(self, ui);
+                });
+                egui::CollapsingHeader::new("DUPLICATES").show(ui, |ui| {
+                    duplicates_section(self, ui);
+                });
+                egui::CollapsingHeader::new("STATS").show(ui, |ui| {
+                    stats_section(self, ui);
+                });
+                egui::CollapsingHeader::new("RECORDER").show(ui, |ui| {
+                    recorder_section(self, ctx, ui);
+                });
+                if let Some(message) = self.export_message.as_ref() {
+                    ui.label(message);
+                }
+                combo_strip(self, ui);
 
                 if let Some(previous) = self.character_selected.as_ref() {
                     self.previous_choice = Some(previous.name.to_owned());
@@ -0,0 +1,81 @@
+// Turning a written combo line into the tokens the game profile knows about.
+//
+// Token patterns come straight from the game json (`attacks` keys plus the
+// default `movement` keys), so they are regex fragments like `LP` or `\(1\)`.
+
+use regex::Regex;
+
+// separators players write between moves, kept as-is by the text view
+pub const SEPARATORS: [&str; 5] = [">", "XX", "~", "->", ","];
+
+pub fn token_regex(patterns: &[String]) -> Result<Regex, regex::Error> {
+    let patterns: Vec<String> = patterns
+        .iter()
+        .map(|p| match p.as_str() {
+            "+" | "*" | "?" | "[" | "]" => format!("\\{}", p),
+            _ => p.to_owned(),
+        })
+        .collect();
+    Regex::new(&patterns.join("|").to_ascii_uppercase())
+}
+
+// Splits `line` into matched tokens, anything the regex doesn't know is kept
+// as single characters so it can still be shown (or flagged) later.
+pub fn tokenize(line: &str, re: &Regex) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut rest = line;
+    while let Some(c) = rest.chars().next() {
+        match re.find(rest) {
+            Some(m) if m.start() == 0 && !m.as_str().is_empty() => {
+                tokens.push(m.as_str().to_owned());
+                rest = &rest[m.end()..];
+            }
+            _ => {
+                tokens.push(c.to_string());
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+    tokens
+}
+
+// Groups tokens into the words written between spaces, `5LK 5MK` becomes
+// `[["5", "LK"], ["5", "MK"]]`.
+pub fn words(tokens: &[String]) -> Vec<Vec<String>> {
+    tokens
+        .split(|t| t.trim().is_empty())
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_vec())
+        .collect()
+}
+
+pub fn is_separator(word: &[String]) -> bool {
+    SEPARATORS.contains(&word.concat().as_str())
+}
+
+// "#4da6ff" or "4da6ff"
+pub fn parse_hex_color(hex: &str) -> Option<[u8; 3]> {
+    let hex = hex.trim().trim_start_matches('#');
+    if hex.len() != 6 || !hex.is_ascii() {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some([channel(0)?, channel(2)?, channel(4)?])
+}
+
+// What the app does to typed inputs, uppercase and commas as spaces, plus
+// collapsing runs of spaces: "5lk, 5mk  2hp" -> "5LK 5MK 2HP".
+pub fn normalize(inputs: &str) -> String {
+    inputs
+        .lines()
+        .map(|line| {
+            line.replace(',', " ")
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ")
+                .to_ascii_uppercase()
+        })
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,152 @@
+// Walking through a combo one input at a time while labbing. Works on the
+// mapped token lines the icon strip draws, spaces and separators are skipped
+// since there is nothing to press for them.
+
+use crate::notation;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Step {
+    // finished the combo, starting over
+    RunComplete,
+    // finished the combo enough times
+    Done,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Practice {
+    // the inputs this was started for, practice stops when they change
+    pub inputs: String,
+    // (line, token) of every input to press, in order
+    pub positions: Vec<(usize, usize)>,
+    pub step: usize,
+    pub runs: u32,
+    pub target_runs: u32,
+    // seconds per input when advancing on its own, None waits for a key
+    pub interval: Option<f32>,
+    elapsed: f32,
+}
+
+impl Practice {
+    pub fn new(inputs: &str, lines: &[Vec<String>], target_runs: u32, interval: f32) -> Self {
+        let mut positions = Vec::new();
+        for (line, tokens) in lines.iter().enumerate() {
+            for (index, token) in tokens.iter().enumerate() {
+                let separator = notation::is_separator(std::slice::from_ref(token));
+                if !token.trim().is_empty() && !separator {
+                    positions.push((line, index));
+                }
+            }
+        }
+        Self {
+            inputs: inputs.to_owned(),
+            positions,
+            step: 0,
+            runs: 0,
+            target_runs: target_runs.max(1),
+            interval: Some(interval).filter(|i| *i > 0.0),
+            elapsed: 0.0,
+        }
+    }
+
+    pub fn current(&self) -> Option<(usize, usize)> {
+        self.positions.get(self.step).copied()
+    }
+
+    // moves the highlight on by hand, wrapping back to the first input at the
+    // end, paging through isn't landing so this never counts a run either
+    pub fn advance(&mut self) {
+        self.elapsed = 0.0;
+        if !self.positions.is_empty() {
+            self.step = (self.step + 1) % self.positions.len();
+        }
+    }
+
+    // the player says they landed the whole run, wherever the highlight is
+    pub fn land(&mut self) -> Step {
+        self.elapsed = 0.0;
+        self.step = 0;
+        self.runs += 1;
+        if self.runs >= self.target_runs {
+            Step::Done
+        } else {
+            Step::RunComplete
+        }
+    }
+
+    // dropped the combo, back to the first input of the same run
+    pub fn miss(&mut self) {
+        self.step = 0;
+        self.elapsed = 0.0;
+    }
+
+    // Moves the timer on by `dt` seconds, moving the highlight when the
+    // interval is up and looping back to the start at the end. Only the player
+    // says whether a run landed, so this never counts one.
+    pub fn tick(&mut self, dt: f32) -> bool {
+        let interval = match self.interval {
+            Some(interval) => interval,
+            None => return false,
+        };
+        self.elapsed += dt;
+        if self.elapsed < interval || self.positions.is_empty() {
+            return false;
+        }
+        self.elapsed = 0.0;
+        self.step = (self.step + 1) % self.positions.len();
+        true
+    }
+
+    // "input 3/12, run 1/5"
+    pub fn status(&self) -> String {
+        format!(
+            "input {}/{}, run {}/{}",
+            (self.step + 1).min(self.positions.len()),
+            self.positions.len(),
+            self.runs + 1,
+            self.target_runs
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines() -> Vec<Vec<String>> {
+        let tokens = ["2", "LK", " ", ">", " ", "5", "HP"];
+        vec![tokens.iter().map(|t| t.to_string()).collect()]
+    }
+
+    #[test]
+    fn skips_spaces_and_separators() {
+        let practice = Practice::new("2LK > 5HP", &lines(), 2, 0.0);
+        assert_eq!(practice.positions, vec![(0, 0), (0, 1), (0, 5), (0, 6)]);
+        assert_eq!(practice.interval, None);
+    }
+
+    #[test]
+    fn counts_runs_only_when_landed() {
+        let mut practice = Practice::new("2LK > 5HP", &lines(), 2, 0.0);
+        for _ in 0..3 {
+            practice.advance();
+        }
+        assert_eq!(practice.current(), Some((0, 6)));
+        practice.advance();
+        assert_eq!(practice.current(), Some((0, 0)));
+        assert_eq!(practice.runs, 0);
+        assert_eq!(practice.land(), Step::RunComplete);
+        assert_eq!(practice.land(), Step::Done);
+    }
+
+    #[test]
+    fn timer_moves_the_highlight_but_never_counts_a_run() {
+        let mut practice = Practice::new("2LK > 5HP", &lines(), 1, 0.5);
+        assert!(!practice.tick(0.25));
+        for _ in 0..20 {
+            practice.tick(0.5);
+        }
+        assert_eq!(practice.runs, 0);
+        assert!(practice.current().is_some());
+        assert_eq!(practice.land(), Step::Done);
+    }
+}
@@ -0,0 +1,151 @@
+// A game profile is an input_<game>.json plus the shared input_default.json
+// movement table and the installed icon packs. This is everything needed to
+// turn written notation into tokens and tokens into images or colours.
+
+use crate::icon_pack::{self, IconPack};
+use crate::notation;
+use serde_json::Value;
+use std::path::PathBuf;
+
+pub const DEFAULT_JSON: &str = include_str!("games/input_default.json");
+
+pub fn default_json() -> Value {
+    serde_json::from_str(DEFAULT_JSON).unwrap()
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Profile<'a> {
+    pub game: &'a Value,
+    pub default: &'a Value,
+    pub packs: &'a [IconPack],
+}
+
+impl<'a> Profile<'a> {
+    // icon packs the game uses, highest priority first
+    pub fn icon_chain(&self) -> Vec<String> {
+        self.game["icon_packs"]
+            .as_array()
+            .map(|packs| {
+                packs
+                    .iter()
+                    .filter_map(|p| p.as_str().map(|p| p.to_owned()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    // every token the regex should look for: the game's attacks, the default
+    // movement and whatever the game's icon packs add on top
+    pub fn known_tokens(&self) -> Vec<String> {
+        let mut tokens: Vec<String> = Vec::new();
+        for table in [&self.game["attacks"], &self.default["movement"]] {
+            if let Some(table) = table.as_object() {
+                tokens.extend(table.keys().cloned());
+            }
+        }
+        let chain = self.icon_chain();
+        for pack in self.packs.iter().filter(|p| chain.contains(&p.id)) {
+            for token in pack.manifest.icons.keys() {
+                let token = regex::escape(token);
+                if !tokens.contains(&token) {
+                    tokens.push(token);
+                }
+            }
+        }
+        tokens
+    }
+
+    // tokens that are buttons to press (LP, HK, P...) rather than directions
+    // or decoration like "(1)" and "+"
+    pub fn buttons(&self) -> Vec<String> {
+        self.known_tokens()
+            .into_iter()
+            .map(|t| t.replace('\\', ""))
+            .filter(|t| t != "J" && !t.is_empty() && t.chars().all(|c| c.is_ascii_alphabetic()))
+            .collect()
+    }
+
+    // the lines of `inputs` as tokens, what the icon strip and exports draw
+    pub fn map_inputs(&self, inputs: &str) -> Result<Vec<Vec<String>>, regex::Error> {
+        let re = notation::token_regex(&self.known_tokens())?;
+        Ok(inputs
+            .split('\n')
+            .map(|line| notation::tokenize(line, &re))
+            .collect())
+    }
+
+    // image path and scale for a token, icon packs first then the game's own mapping
+    pub fn icon_path(&self, token: &str) -> Option<(PathBuf, f32)> {
+        let plain = token.replace('\\', "");
+        if let Some(found) = icon_pack::resolve(self.packs, &self.icon_chain(), &plain) {
+            return Some(found);
+        }
+        let escaped = regex::escape(token);
+        self.game["attacks"]
+            .get(token)
+            .or(self.game["attacks"].get(&escaped))
+            .or(self.default["movement"].get(token))
+            .and_then(|v| v.as_str())
+            .filter(|v| *v != "skip")
+            .map(|v| {
+                // stored with windows separators, "skg\\Sg_hk.png"
+                let path = v
+                    .split('\\')
+                    .fold(PathBuf::from(icon_pack::IMAGES), |p, part| p.join(part));
+                (path, 1.0)
+            })
+    }
+
+    // colour for a token from the game's "colors" table, e.g. "LP": "#4da6ff"
+    pub fn color(&self, token: &str) -> Option<[u8; 3]> {
+        notation::parse_hex_color(self.game["colors"].get(token)?.as_str()?)
+    }
+
+    // Problems with the profile itself: missing tables, icon packs that aren't
+    // installed, images that don't exist, colours that don't parse.
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if !self.game["attacks"].is_object() {
+            problems.push("no \"attacks\" object".to_owned());
+        }
+        if let Err(e) = notation::token_regex(&self.known_tokens()) {
+            problems.push(format!("token patterns don't compile: {}", e));
+        }
+        for id in self.icon_chain() {
+            if !self.packs.iter().any(|p| p.id == id) {
+                problems.push(format!("icon pack '{}' is not installed", id));
+            }
+        }
+        for token in self.known_tokens() {
+            match self.icon_path(&token) {
+                Some((path, _)) if !path.is_file() => {
+                    problems.push(format!("'{}' image {} is missing", token, path.display()))
+                }
+                _ => {}
+            }
+        }
+        if let Some(colors) = self.game["colors"].as_object() {
+            for (token, hex) in colors {
+                if hex.as_str().and_then(notation::parse_hex_color).is_none() {
+                    problems.push(format!("'{}' colour {} is not #rrggbb", token, hex));
+                }
+            }
+        }
+        problems
+    }
+}
+
+// A profile with the six classic buttons and the default movement table, for
+// tests that need notation mapped.
+#[cfg(test)]
+pub(crate) fn with_test_profile<T>(f: impl FnOnce(&Profile) -> T) -> T {
+    let game = serde_json::json!({
+        "attacks": { "LP": "", "MP": "", "HP": "", "LK": "", "MK": "", "HK": "" }
+    });
+    let default = default_json();
+    f(&Profile {
+        game: &game,
+        default: &default,
+        packs: &[],
+    })
+}
@@ -0,0 +1,470 @@
+// The search box's query language, matched against a combo's parsed moves
+// rather than its text:
+//
+//   starts:2LK and motion:236        words next to each other are "and"
+//   has:hold or has:jump             "or" binds looser than "and"
+//   not tag:corner  -state:done      "not" or a leading "-"
+//   (ends:super or ends:236LP+LK) length < 8
+//   damage >= 3000  "bnb route"      numbers from the metadata, quoted phrases
+//
+// Fields:
+//   game: char: tag: state: name: notes:   text of the combo
+//   starts: ends:    the first / last moves, by notation or frame data kind
+//   has:             hold, jump, cancel, a frame data kind, or notation
+//   motion:236       any move with that motion, whatever the button
+//   button:LP        any move pressing that button
+//
+// Numbers compare with < <= = >= > : length (moves), hits, damage, meter,
+// undizzy.
+
+use crate::combo::ComboState;
+use crate::recorder::Link;
+use crate::search::{self, Entry};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Op {
+    Lt,
+    Le,
+    Eq,
+    Ge,
+    Gt,
+}
+
+impl Op {
+    fn parse(s: &str) -> Option<Op> {
+        match s {
+            "<" => Some(Op::Lt),
+            "<=" => Some(Op::Le),
+            "=" | "==" => Some(Op::Eq),
+            ">=" => Some(Op::Ge),
+            ">" => Some(Op::Gt),
+            _ => None,
+        }
+    }
+
+    fn holds(&self, a: f64, b: f64) -> bool {
+        match self {
+            Op::Lt => a < b,
+            Op::Le => a <= b,
+            Op::Eq => a == b,
+            Op::Ge => a >= b,
+            Op::Gt => a > b,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Query {
+    And(Vec<Query>),
+    Or(Vec<Query>),
+    Not(Box<Query>),
+    // a plain word or quoted phrase
+    Text(String),
+    Field(String, String),
+    Compare(String, Op, f64),
+}
+
+const FIELDS: [&str; 14] = [
+    "game",
+    "char",
+    "character",
+    "tag",
+    "state",
+    "name",
+    "notes",
+    "starts",
+    "ends",
+    "has",
+    "contains",
+    "uses",
+    "motion",
+    "button",
+];
+const NUMBERS: [&str; 6] = ["length", "len", "hits", "damage", "meter", "undizzy"];
+
+#[derive(Debug, Clone, PartialEq)]
+enum Lexeme {
+    Open,
+    Close,
+    Op(Op),
+    Word(String),
+    Phrase(String),
+}
+
+fn lex(text: &str) -> Result<Vec<Lexeme>, String> {
+    let mut lexemes = Vec::new();
+    let mut chars = text.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' => {
+                chars.next();
+                lexemes.push(Lexeme::Open);
+            }
+            ')' => {
+                chars.next();
+                lexemes.push(Lexeme::Close);
+            }
+            '"' => {
+                chars.next();
+                let phrase: String = chars.by_ref().take_while(|c| *c != '"').collect();
+                lexemes.push(Lexeme::Phrase(phrase));
+            }
+            '<' | '>' | '=' => {
+                let mut op = String::new();
+                while let Some(&c) = chars.peek() {
+                    if !"<>=".contains(c) {
+                        break;
+                    }
+                    op.push(c);
+                    chars.next();
+                }
+                let parsed = Op::parse(&op).ok_or_else(|| format!("unknown operator '{}'", op))?;
+                lexemes.push(Lexeme::Op(parsed));
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some(&c) = chars.peek() {
+                    // "->" is a separator, not the start of a comparison
+                    if c.is_whitespace()
+                        || "()\"<=".contains(c)
+                        || (c == '>' && !word.ends_with('-'))
+                    {
+                        break;
+                    }
+                    word.push(c);
+                    chars.next();
+                }
+                lexemes.push(Lexeme::Word(word));
+            }
+        }
+    }
+    Ok(lexemes)
+}
+
+struct Parser {
+    lexemes: Vec<Lexeme>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Lexeme> {
+        self.lexemes.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Lexeme> {
+        let next = self.lexemes.get(self.position).cloned();
+        self.position += 1;
+        next
+    }
+
+    fn keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Lexeme::Word(w)) if w.eq_ignore_ascii_case(keyword))
+    }
+
+    fn or(&mut self) -> Result<Query, String> {
+        let mut any = vec![self.and()?];
+        while self.keyword("or") {
+            self.next();
+            any.push(self.and()?);
+        }
+        Ok(if any.len() == 1 {
+            any.remove(0)
+        } else {
+            Query::Or(any)
+        })
+    }
+
+    fn and(&mut self) -> Result<Query, String> {
+        let mut all = vec![self.unary()?];
+        loop {
+            if self.keyword("and") {
+                self.next();
+            } else if self.keyword("or") || matches!(self.peek(), None | Some(Lexeme::Close)) {
+                break;
+            }
+            all.push(self.unary()?);
+        }
+        Ok(if all.len() == 1 {
+            all.remove(0)
+        } else {
+            Query::And(all)
+        })
+    }
+
+    fn unary(&mut self) -> Result<Query, String> {
+        match self.next() {
+            Some(Lexeme::Open) => {
+                let inner = self.or()?;
+                match self.next() {
+                    Some(Lexeme::Close) => Ok(inner),
+                    _ => Err("missing ')'".to_owned()),
+                }
+            }
+            Some(Lexeme::Word(w)) if w.eq_ignore_ascii_case("not") => {
+                Ok(Query::Not(Box::new(self.unary()?)))
+            }
+            Some(Lexeme::Word(w)) if w.len() > 1 && w.starts_with('-') && !w.starts_with("->") => {
+                Ok(Query::Not(Box::new(self.word(w[1..].to_owned())?)))
+            }
+            Some(Lexeme::Word(w)) => self.word(w),
+            Some(Lexeme::Phrase(phrase)) => Ok(Query::Text(phrase)),
+            Some(Lexeme::Close) => Err("unexpected ')'".to_owned()),
+            Some(Lexeme::Op(_)) => {
+                Err("a comparison needs a field first, like length < 8".to_owned())
+            }
+            None => Err("query ends too early".to_owned()),
+        }
+    }
+
+    fn word(&mut self, word: String) -> Result<Query, String> {
+        if let Some(Lexeme::Op(op)) = self.peek().cloned() {
+            self.next();
+            let field = word.to_ascii_lowercase();
+            if !NUMBERS.contains(&field.as_str()) {
+                return Err(format!(
+                    "can't compare '{}', numbers are {}",
+                    word,
+                    NUMBERS.join(", ")
+                ));
+            }
+            let value = match self.next() {
+                Some(Lexeme::Word(value)) => value
+                    .parse::<f64>()
+                    .map_err(|_| format!("'{}' isn't a number", value))?,
+                _ => return Err(format!("{} needs a number to compare with", word)),
+            };
+            return Ok(Query::Compare(field, op, value));
+        }
+        match word.split_once(':') {
+            Some((field, value)) => {
+                let field = field.to_ascii_lowercase();
+                if !FIELDS.contains(&field.as_str()) {
+                    return Err(format!("unknown field '{}:'", field));
+                }
+                Ok(Query::Field(field, value.to_owned()))
+            }
+            None => Ok(Query::Text(word)),
+        }
+    }
+}
+
+pub fn parse(text: &str) -> Result<Query, String> {
+    let mut parser = Parser {
+        lexemes: lex(text)?,
+        position: 0,
+    };
+    let query = parser.or()?;
+    match parser.peek() {
+        None => Ok(query),
+        Some(_) => Err("unexpected ')'".to_owned()),
+    }
+}
+
+fn contains(haystack: &str, needle: &str) -> bool {
+    haystack.to_lowercase().contains(&needle.to_lowercase())
+}
+
+fn is_kind(kind: Option<&String>, text: &str) -> bool {
+    matches!(kind, Some(k) if !k.is_empty() && k.eq_ignore_ascii_case(text))
+}
+
+impl Query {
+    pub fn matches(&self, entry: &Entry) -> bool {
+        let combo = &entry.combo;
+        let written = entry.compact();
+        match self {
+            Query::And(all) => all.iter().all(|q| q.matches(entry)),
+            Query::Or(any) => any.iter().any(|q| q.matches(entry)),
+            Query::Not(inner) => !inner.matches(entry),
+            Query::Text(text) => {
+                contains(&combo.name, text)
+                    || contains(&combo.notes, text)
+                    || combo.tags.iter().any(|t| contains(t, text))
+                    || contains(&combo.inputs, text)
+                    || written.contains(&search::compact(text))
+            }
+            Query::Compare(field, op, value) => {
+                let actual = match field.as_str() {
+                    "length" | "len" => Some(entry.moves.len() as f64),
+                    _ => combo.metadata.get(field).and_then(|v| v.as_f64()),
+                };
+                // a combo without the number doesn't match either way
+                matches!(actual, Some(actual) if op.holds(actual, *value))
+            }
+            Query::Field(field, value) => match field.as_str() {
+                "game" => contains(&entry.game, value) || entry.game_id.eq_ignore_ascii_case(value),
+                "char" | "character" => contains(&entry.character, value),
+                "tag" => combo.tags.iter().any(|t| t.eq_ignore_ascii_case(value)),
+                "state" => ComboState::parse(value) == Some(combo.state),
+                "name" => contains(&combo.name, value),
+                "notes" => contains(&combo.notes, value),
+                "starts" => {
+                    written.starts_with(&search::compact(value))
+                        || is_kind(entry.kinds.first(), value)
+                }
+                "ends" => {
+                    written.ends_with(&search::compact(value)) || is_kind(entry.kinds.last(), value)
+                }
+                "motion" => entry
+                    .moves
+                    .iter()
+                    .any(|m| m.motion.iter().map(|d| d.to_string()).collect::<String>() == *value),
+                "button" => entry
+                    .moves
+                    .iter()
+                    .any(|m| m.buttons.iter().any(|b| b.eq_ignore_ascii_case(value))),
+                // has, contains, uses
+                _ => match value.to_ascii_lowercase().as_str() {
+                    "hold" => entry.tokens.iter().any(|t| t == "["),
+                    "jump" => entry.moves.iter().any(|m| m.jump),
+                    "cancel" => entry.moves.iter().any(|m| m.link == Link::Cancel),
+                    _ => {
+                        entry.kinds.iter().any(|k| is_kind(Some(k), value))
+                            || written.contains(&search::compact(value))
+                    }
+                },
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::combo::Combo;
+    use crate::recorder::Move;
+
+    fn mv(motion: &[u8], button: &str, link: Link) -> Move {
+        Move {
+            text: format!(
+                "{}{}",
+                motion.iter().map(|d| d.to_string()).collect::<String>(),
+                button
+            ),
+            motion: motion.to_vec(),
+            buttons: vec![button.to_owned()],
+            jump: false,
+            link,
+        }
+    }
+
+    // 2LK 2MP XX 236LP, a corner bnb worth 2400
+    fn entry() -> Entry {
+        let mut combo = Combo::new(
+            "Corner BnB".to_owned(),
+            "2LK 2MP XX 236LP".to_owned(),
+            ComboState::Done,
+        );
+        combo.tags = vec!["corner".to_owned()];
+        combo.metadata.insert("damage".to_owned(), 2400.into());
+        Entry {
+            game: "Street Fighter".to_owned(),
+            game_id: "sf".to_owned(),
+            character: "Ryu".to_owned(),
+            index: 0,
+            combo,
+            tokens: Vec::new(),
+            moves: vec![
+                mv(&[2], "LK", Link::Start),
+                mv(&[2], "MP", Link::Link),
+                mv(&[2, 3, 6], "LP", Link::Cancel),
+            ],
+            kinds: vec![
+                "normal".to_owned(),
+                "normal".to_owned(),
+                "special".to_owned(),
+            ],
+        }
+    }
+
+    fn found(query: &str) -> bool {
+        parse(query).unwrap().matches(&entry())
+    }
+
+    #[test]
+    fn precedence() {
+        let field = |f: &str, v: &str| Query::Field(f.to_owned(), v.to_owned());
+        assert_eq!(
+            parse("tag:a tag:b or tag:c").unwrap(),
+            Query::Or(vec![
+                Query::And(vec![field("tag", "a"), field("tag", "b")]),
+                field("tag", "c"),
+            ])
+        );
+        assert_eq!(
+            parse("tag:a and (tag:b or tag:c)").unwrap(),
+            Query::And(vec![
+                field("tag", "a"),
+                Query::Or(vec![field("tag", "b"), field("tag", "c")]),
+            ])
+        );
+        assert_eq!(
+            parse("-tag:a").unwrap(),
+            Query::Not(Box::new(field("tag", "a")))
+        );
+        assert_eq!(
+            parse("length <= 8").unwrap(),
+            Query::Compare("length".to_owned(), Op::Le, 8.0)
+        );
+        assert_eq!(
+            parse("\"bnb route\"").unwrap(),
+            Query::Text("bnb route".to_owned())
+        );
+    }
+
+    #[test]
+    fn separators_are_not_comparisons() {
+        assert_eq!(
+            parse("2LK->2MP").unwrap(),
+            Query::Text("2LK->2MP".to_owned())
+        );
+        assert_eq!(parse("->").unwrap(), Query::Text("->".to_owned()));
+    }
+
+    #[test]
+    fn errors() {
+        assert!(parse("").is_err());
+        assert!(parse("(tag:a").is_err());
+        assert!(parse("tag:a)").is_err());
+        assert!(parse("colour:red").is_err());
+        assert!(parse("name < 3").is_err());
+        assert!(parse("damage > lots").is_err());
+        assert!(parse("damage =< 3").is_err());
+        assert!(parse("< 3").is_err());
+    }
+
+    #[test]
+    fn fields() {
+        assert!(found("starts:2LK ends:236LP"));
+        assert!(found("starts:normal ends:special"));
+        assert!(!found("starts:2MP"));
+        assert!(found("motion:236 button:mp"));
+        assert!(!found("motion:623"));
+        assert!(found("has:cancel"));
+        assert!(!found("has:jump or has:hold"));
+        assert!(found("has:special has:2MPXX"));
+        assert!(found("game:street game:SF char:ryu"));
+        assert!(found("state:done tag:Corner name:bnb"));
+        assert!(found("bnb"));
+    }
+
+    #[test]
+    fn numbers() {
+        assert!(found("length = 3"));
+        assert!(found("damage >= 2400 damage < 2401"));
+        assert!(!found("damage > 2400"));
+        // no meter in the metadata, neither side matches
+        assert!(!found("meter > 0"));
+        assert!(!found("meter <= 0"));
+    }
+
+    #[test]
+    fn negation() {
+        assert!(found("not tag:midscreen"));
+        assert!(!found("-tag:corner"));
+        assert!(found("-(tag:corner and state:learning) or motion:236"));
+    }
+}
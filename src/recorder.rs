@@ -0,0 +1,607 @@
+// Inputs as a stream of timed events, and checking that stream against a
+// written combo. Events come from the keyboard in the window or from an event
+// log, a plain text file so runs can be saved, replayed and written by hand:
+//
+//     # frame event
+//     0 dir 2
+//     3 dir 6
+//     4 press LP
+//     7 release LP
+//
+// Directions are numpad notation (5 is neutral), frames are 1/60s.
+
+use crate::notation;
+use std::fmt;
+
+pub const FPS: f32 = 60.0;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EventKind {
+    Direction(u8),
+    Press(String),
+    Release(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct InputEvent {
+    pub frame: u32,
+    pub kind: EventKind,
+}
+
+pub fn parse_log(text: &str) -> Result<Vec<InputEvent>, String> {
+    let mut events: Vec<InputEvent> = Vec::new();
+    for (number, line) in text.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+        let error = || {
+            format!(
+                "line {}: expected '<frame> dir|press|release <value>'",
+                number + 1
+            )
+        };
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        let (frame, kind, value) = match parts.as_slice() {
+            [frame, kind, value] => (frame.parse::<u32>().map_err(|_| error())?, *kind, *value),
+            _ => return Err(error()),
+        };
+        let kind = match kind.to_ascii_lowercase().as_str() {
+            "dir" => match value.parse::<u8>() {
+                Ok(dir @ 1..=9) => EventKind::Direction(dir),
+                _ => return Err(format!("line {}: direction must be 1-9", number + 1)),
+            },
+            "press" => EventKind::Press(value.to_ascii_uppercase()),
+            "release" => EventKind::Release(value.to_ascii_uppercase()),
+            _ => return Err(error()),
+        };
+        if matches!(events.last(), Some(e) if e.frame > frame) {
+            return Err(format!("line {}: frames must not go backwards", number + 1));
+        }
+        events.push(InputEvent { frame, kind });
+    }
+    Ok(events)
+}
+
+pub fn format_log(events: &[InputEvent]) -> String {
+    let mut out = "# frame event\n".to_owned();
+    for event in events {
+        let (kind, value) = match &event.kind {
+            EventKind::Direction(dir) => ("dir", dir.to_string()),
+            EventKind::Press(button) => ("press", button.to_owned()),
+            EventKind::Release(button) => ("release", button.to_owned()),
+        };
+        out.push_str(&format!("{} {} {}\n", event.frame, kind, value));
+    }
+    out
+}
+
+// numpad direction for held up/down/left/right, both of a pair cancel out
+pub fn direction(up: bool, down: bool, left: bool, right: bool) -> u8 {
+    let vertical = match (up, down) {
+        (true, false) => 6,
+        (false, true) => 0,
+        _ => 3,
+    };
+    let horizontal = match (left, right) {
+        (true, false) => 1,
+        (false, true) => 3,
+        _ => 2,
+    };
+    vertical + horizontal
+}
+
+// Builds an event stream from key presses, up/down/left/right keys become
+// numpad directions.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Recorder {
+    pub events: Vec<InputEvent>,
+    // up, down, left, right
+    held: [bool; 4],
+}
+
+impl Recorder {
+    pub const UP: usize = 0;
+    pub const DOWN: usize = 1;
+    pub const LEFT: usize = 2;
+    pub const RIGHT: usize = 3;
+
+    pub fn direction_key(&mut self, frame: u32, key: usize, down: bool) {
+        let before = self.direction();
+        self.held[key] = down;
+        let after = self.direction();
+        if after != before {
+            self.push(frame, EventKind::Direction(after));
+        }
+    }
+
+    pub fn button(&mut self, frame: u32, button: &str, pressed: bool) {
+        let button = button.to_owned();
+        let kind = if pressed {
+            EventKind::Press(button)
+        } else {
+            EventKind::Release(button)
+        };
+        self.push(frame, kind);
+    }
+
+    fn direction(&self) -> u8 {
+        let [up, down, left, right] = self.held;
+        direction(up, down, left, right)
+    }
+
+    fn push(&mut self, frame: u32, kind: EventKind) {
+        // frames never go backwards, whatever the clock did
+        let frame = frame.max(self.events.last().map_or(0, |e| e.frame));
+        self.events.push(InputEvent { frame, kind });
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Link {
+    // first move of the combo
+    Start,
+    // ">" or ",", the previous move has to recover first
+    Link,
+    // "XX", "~" or "->", pressed while the previous move is still active
+    Cancel,
+}
+
+impl Link {
+    fn of(separator: &str) -> Link {
+        match separator {
+            "XX" | "~" | "->" => Link::Cancel,
+            _ => Link::Link,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Link::Start => "start",
+            Link::Link => "link",
+            Link::Cancel => "cancel",
+        }
+    }
+}
+
+// one written move, "236LP" is motion [2, 3, 6] and buttons ["LP"]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Move {
+    pub text: String,
+    pub motion: Vec<u8>,
+    pub buttons: Vec<String>,
+    pub jump: bool,
+    pub link: Link,
+}
+
+// `buttons` are the tokens that are buttons (the game's attacks), anything
+// else that isn't a direction, "J" or a separator is decoration like "(1)".
+pub fn moves(lines: &[Vec<String>], buttons: &[String]) -> Vec<Move> {
+    let mut moves = Vec::new();
+    let mut link = Link::Start;
+    for tokens in lines {
+        for word in notation::words(tokens) {
+            if notation::is_separator(&word) {
+                link = Link::of(&word.concat());
+                continue;
+            }
+            // "214P~P" is two moves with a cancel in between
+            for part in word.split_inclusive(|t| notation::is_separator(std::slice::from_ref(t))) {
+                let (part, separator) = match part.split_last() {
+                    Some((last, rest)) if notation::is_separator(std::slice::from_ref(last)) => {
+                        (rest, Some(last))
+                    }
+                    _ => (part, None),
+                };
+                if let Some(next) = parse_move(part, buttons, link) {
+                    moves.push(next);
+                    link = Link::Link;
+                }
+                if let Some(separator) = separator {
+                    link = Link::of(separator);
+                }
+            }
+        }
+    }
+    moves
+}
+
+fn parse_move(tokens: &[String], buttons: &[String], link: Link) -> Option<Move> {
+    let mut next = Move {
+        text: tokens.concat(),
+        motion: Vec::new(),
+        buttons: Vec::new(),
+        jump: false,
+        link,
+    };
+    for token in tokens {
+        match token.as_str() {
+            "J" => next.jump = true,
+            t if t.len() == 1 && t.chars().all(|c| ('1'..='9').contains(&c)) => {
+                next.motion.push(t.parse().unwrap())
+            }
+            t if buttons.iter().any(|b| b == t) => next.buttons.push(t.to_owned()),
+            _ => {}
+        }
+    }
+    // a bare direction (dash, jump) has no press to check
+    if next.buttons.is_empty() {
+        None
+    } else {
+        Some(next)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MatchOptions {
+    // most frames between two presses for a link / a cancel
+    pub link_window: u32,
+    pub cancel_window: u32,
+    // buttons of "LP+LK" have to be pressed this close together
+    pub together: u32,
+}
+
+impl Default for MatchOptions {
+    fn default() -> Self {
+        Self {
+            link_window: 40,
+            cancel_window: 20,
+            together: 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Miss {
+    // index into the moves
+    pub index: usize,
+    pub previous: Option<String>,
+    pub expected: String,
+    pub link: Link,
+    pub reason: String,
+}
+
+impl fmt::Display for Miss {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.previous {
+            Some(previous) => write!(
+                f,
+                "missed the {} {} -> {}: {}",
+                self.link.name(),
+                previous,
+                self.expected,
+                self.reason
+            ),
+            None => write!(
+                f,
+                "missed the first move {}: {}",
+                self.expected, self.reason
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchReport {
+    pub matched: usize,
+    pub total: usize,
+    pub miss: Option<Miss>,
+}
+
+// "P" and "K" in a combo mean any punch or kick
+fn button_matches(expected: &str, pressed: &str) -> bool {
+    expected == pressed || (expected.len() == 1 && pressed.ends_with(expected))
+}
+
+// Walks the presses in `events` against `moves` in order. Every press has to
+// be the next move's button, with its motion in the directions since the last
+// move and within the link or cancel window.
+pub fn check(moves: &[Move], events: &[InputEvent], options: &MatchOptions) -> MatchReport {
+    let mut report = MatchReport {
+        matched: 0,
+        total: moves.len(),
+        miss: None,
+    };
+    let mut directions: Vec<u8> = vec![5];
+    let mut held = 5;
+    let mut last_press: Option<u32> = None;
+    let mut events = events.iter().peekable();
+    for (index, expected) in moves.iter().enumerate() {
+        let miss = |reason: String| Miss {
+            index,
+            previous: index.checked_sub(1).map(|i| moves[i].text.clone()),
+            expected: expected.text.clone(),
+            link: expected.link,
+            reason,
+        };
+        // directions up to the first press
+        let press = loop {
+            match events.next() {
+                Some(InputEvent {
+                    kind: EventKind::Direction(dir),
+                    ..
+                }) => {
+                    held = *dir;
+                    directions.push(*dir);
+                }
+                Some(InputEvent {
+                    frame,
+                    kind: EventKind::Press(button),
+                }) => break Some((*frame, button)),
+                Some(_) => {}
+                None => break None,
+            }
+        };
+        let (frame, button) = match press {
+            Some(press) => press,
+            None => {
+                report.miss = Some(miss("no more inputs".to_owned()));
+                return report;
+            }
+        };
+        let mut pressed = vec![button.to_owned()];
+        // the other buttons of a "LP+LK"
+        while pressed.len() < expected.buttons.len() {
+            match events.peek() {
+                Some(InputEvent {
+                    frame: next,
+                    kind: EventKind::Press(button),
+                }) if next - frame <= options.together => {
+                    pressed.push(button.to_owned());
+                    events.next();
+                }
+                _ => break,
+            }
+        }
+        let buttons_ok = expected.buttons.len() == pressed.len()
+            && expected
+                .buttons
+                .iter()
+                .all(|e| pressed.iter().any(|p| button_matches(e, p)));
+        if !buttons_ok {
+            report.miss = Some(miss(format!("pressed {}", pressed.join("+"))));
+            return report;
+        }
+        if let Some(last) = last_press {
+            let window = match expected.link {
+                Link::Cancel => options.cancel_window,
+                _ => options.link_window,
+            };
+            if frame - last > window {
+                report.miss = Some(miss(format!(
+                    "{} frames late ({}f after, window {}f)",
+                    frame - last - window,
+                    frame - last,
+                    window
+                )));
+                return report;
+            }
+        }
+        if !motion_done(&expected.motion, &directions, held) {
+            report.miss = Some(miss(format!(
+                "motion {} not input (got {})",
+                expected
+                    .motion
+                    .iter()
+                    .map(|d| d.to_string())
+                    .collect::<String>(),
+                directions.iter().map(|d| d.to_string()).collect::<String>()
+            )));
+            return report;
+        }
+        if expected.jump && !directions.iter().any(|d| *d >= 7) {
+            report.miss = Some(miss("not jumping".to_owned()));
+            return report;
+        }
+        report.matched += 1;
+        last_press = Some(frame);
+        directions = vec![held];
+    }
+    report
+}
+
+// the motion's directions appear in order and the last one is still held
+// ("2" is a held down, "236" is down, down-forward, forward)
+fn motion_done(motion: &[u8], directions: &[u8], held: u8) -> bool {
+    let last = match motion.last() {
+        Some(last) => *last,
+        None => return true,
+    };
+    if held != last {
+        return false;
+    }
+    let mut wanted = motion.iter().peekable();
+    for dir in directions {
+        if wanted.peek() == Some(&dir) {
+            wanted.next();
+        }
+    }
+    wanted.peek().is_none()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BUTTONS: [&str; 8] = ["LP", "MP", "HP", "LK", "MK", "HK", "P", "K"];
+
+    fn moves_of(combo: &str) -> Vec<Move> {
+        let mut patterns: Vec<String> = BUTTONS.iter().map(|b| b.to_string()).collect();
+        patterns.extend(["J", "XX", "~", "+", " "].iter().map(|t| t.to_string()));
+        patterns.extend((1..=9).map(|d| d.to_string()));
+        let re = notation::token_regex(&patterns).unwrap();
+        let buttons: Vec<String> = BUTTONS.iter().map(|b| b.to_string()).collect();
+        moves(&[notation::tokenize(combo, &re)], &buttons)
+    }
+
+    fn log(text: &str) -> Vec<InputEvent> {
+        parse_log(text).unwrap()
+    }
+
+    #[test]
+    fn parses_logs_with_comments() {
+        let events = log("# frame event\n0 dir 2\n\n3 dir 6 # forward\n4 press lp\n7 release LP\n");
+        assert_eq!(
+            events,
+            vec![
+                InputEvent {
+                    frame: 0,
+                    kind: EventKind::Direction(2)
+                },
+                InputEvent {
+                    frame: 3,
+                    kind: EventKind::Direction(6)
+                },
+                InputEvent {
+                    frame: 4,
+                    kind: EventKind::Press("LP".to_owned())
+                },
+                InputEvent {
+                    frame: 7,
+                    kind: EventKind::Release("LP".to_owned())
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_bad_lines() {
+        assert!(parse_log("0 press").unwrap_err().starts_with("line 1:"));
+        assert!(parse_log("x press LP").is_err());
+        assert!(parse_log("0 kick LP").is_err());
+        assert!(parse_log("0 dir 0").unwrap_err().contains("1-9"));
+        assert!(parse_log("0 dir 10").is_err());
+    }
+
+    #[test]
+    fn rejects_frames_going_backwards() {
+        let error = parse_log("5 press LP\n4 release LP").unwrap_err();
+        assert_eq!(error, "line 2: frames must not go backwards");
+        // the same frame is fine
+        assert!(parse_log("5 press LP\n5 press LK").is_ok());
+    }
+
+    #[test]
+    fn format_round_trips() {
+        let events = log("0 dir 2\n1 dir 3\n2 dir 6\n2 press LP\n9 release LP\n");
+        assert_eq!(parse_log(&format_log(&events)).unwrap(), events);
+    }
+
+    #[test]
+    fn opposite_directions_cancel() {
+        assert_eq!(direction(false, false, false, false), 5);
+        assert_eq!(direction(true, true, false, false), 5);
+        assert_eq!(direction(false, false, true, true), 5);
+        assert_eq!(direction(true, true, true, true), 5);
+        assert_eq!(direction(false, true, true, true), 2);
+        assert_eq!(direction(true, false, false, true), 9);
+        assert_eq!(direction(false, true, true, false), 1);
+    }
+
+    #[test]
+    fn recorder_turns_keys_into_directions() {
+        let mut recorder = Recorder::default();
+        recorder.direction_key(0, Recorder::DOWN, true);
+        recorder.direction_key(2, Recorder::RIGHT, true);
+        recorder.direction_key(4, Recorder::DOWN, false);
+        recorder.button(1, "LP", true);
+        let dirs: Vec<&EventKind> = recorder.events.iter().map(|e| &e.kind).collect();
+        assert_eq!(
+            dirs,
+            vec![
+                &EventKind::Direction(2),
+                &EventKind::Direction(3),
+                &EventKind::Direction(6),
+                &EventKind::Press("LP".to_owned()),
+            ]
+        );
+        // the clock went back, the event didn't
+        assert_eq!(recorder.events[3].frame, 4);
+    }
+
+    #[test]
+    fn a_clean_run_matches() {
+        let moves = moves_of("2LK 2MP XX 236LP");
+        let events = log("0 dir 2\n1 press LK\n15 press MP\n18 dir 3\n19 dir 6\n20 press LP");
+        let report = check(&moves, &events, &MatchOptions::default());
+        assert_eq!((report.matched, report.total, report.miss), (3, 3, None));
+    }
+
+    #[test]
+    fn a_late_link_misses() {
+        let moves = moves_of("5LP 5HP");
+        let events = log("0 press LP\n50 press HP");
+        let miss = check(&moves, &events, &MatchOptions::default())
+            .miss
+            .unwrap();
+        assert_eq!((miss.index, miss.link), (1, Link::Link));
+        assert_eq!(miss.reason, "10 frames late (50f after, window 40f)");
+    }
+
+    #[test]
+    fn cancels_use_the_cancel_window() {
+        let moves = moves_of("5MP XX 5HP");
+        assert_eq!(moves[1].link, Link::Cancel);
+        let options = MatchOptions::default();
+        let in_time = log("0 press MP\n20 press HP");
+        assert_eq!(check(&moves, &in_time, &options).matched, 2);
+        // fine as a link, too slow for a cancel
+        let slow = log("0 press MP\n30 press HP");
+        let miss = check(&moves, &slow, &options).miss.unwrap();
+        assert_eq!(miss.link, Link::Cancel);
+        assert!(miss.reason.contains("window 20f"));
+    }
+
+    #[test]
+    fn buttons_pressed_together() {
+        let moves = moves_of("5LP+LK");
+        assert_eq!(moves[0].buttons, vec!["LP", "LK"]);
+        let options = MatchOptions::default();
+        let together = log("0 press LK\n2 press LP");
+        assert_eq!(check(&moves, &together, &options).matched, 1);
+        let apart = log("0 press LK\n10 press LP");
+        let miss = check(&moves, &apart, &options).miss.unwrap();
+        assert_eq!(miss.reason, "pressed LK");
+    }
+
+    #[test]
+    fn a_missed_motion() {
+        let moves = moves_of("236LP");
+        let events = log("0 dir 2\n2 dir 6\n3 press LP");
+        let miss = check(&moves, &events, &MatchOptions::default())
+            .miss
+            .unwrap();
+        assert_eq!(miss.reason, "motion 236 not input (got 526)");
+        // letting go of the last direction before pressing misses it too
+        let released = log("0 dir 2\n1 dir 3\n2 dir 6\n3 dir 5\n4 press LP");
+        assert!(check(&moves, &released, &MatchOptions::default())
+            .miss
+            .is_some());
+    }
+
+    #[test]
+    fn p_and_k_are_wildcards() {
+        let moves = moves_of("214P ~ K");
+        let events = log("0 dir 2\n1 dir 1\n2 dir 4\n3 press HP\n10 press MK");
+        assert_eq!(check(&moves, &events, &MatchOptions::default()).matched, 2);
+        // but a kick isn't a punch
+        let wrong = log("0 dir 2\n1 dir 1\n2 dir 4\n3 press HK");
+        let miss = check(&moves, &wrong, &MatchOptions::default())
+            .miss
+            .unwrap();
+        assert_eq!(miss.reason, "pressed HK");
+    }
+
+    #[test]
+    fn running_out_of_inputs() {
+        let moves = moves_of("5LP 5MP");
+        let miss = check(&moves, &log("0 press LP"), &MatchOptions::default())
+            .miss
+            .unwrap();
+        assert_eq!(miss.index, 1);
+        assert_eq!(
+            miss.to_string(),
+            "missed the link 5LP -> 5MP: no more inputs"
+        );
+    }
+}
@@ -0,0 +1,315 @@
+// CPU side rendering of a combo to an image, the same icon strip the window
+// shows but laid out with the `image` crate so it works without a GPU.
+
+use ab_glyph::{point, Font, FontVec, PxScale, ScaleFont};
+use image::imageops::FilterType;
+use image::{Rgba, RgbaImage};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+pub const EXPORTS: &str = "exports";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportOptions {
+    // icons wrap onto a new row past this width, like `horizontal_wrapped`
+    pub max_width: u32,
+    pub padding: u32,
+    pub spacing: u32,
+    pub font_size: f32,
+    pub background: [u8; 4],
+    pub text_color: [u8; 4],
+    pub separator_color: [u8; 4],
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self {
+            max_width: 340,
+            padding: 8,
+            spacing: 4,
+            font_size: 16.0,
+            background: [27, 27, 27, 255],
+            text_color: [220, 220, 220, 255],
+            separator_color: [60, 60, 60, 255],
+        }
+    }
+}
+
+// What goes on the image: a title, a few lines of metadata (game, character,
+// state...) and the tokenized combo lines.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ComboStrip {
+    pub title: String,
+    pub metadata: Vec<String>,
+    pub lines: Vec<Vec<String>>,
+}
+
+enum Item {
+    Icon(usize, u32, u32),
+    Text(String, u32, u32),
+    Separator(u32),
+}
+
+// `icon` maps a token to an image path and scale, same as the window does it.
+pub fn render_combo<F>(
+    strip: &ComboStrip,
+    icon: F,
+    options: &ExportOptions,
+) -> Result<RgbaImage, String>
+where
+    F: Fn(&str) -> Option<(PathBuf, f32)>,
+{
+    let font = default_font()?;
+    let scaled = font.as_scaled(PxScale::from(options.font_size));
+    let line_height = (scaled.height() + scaled.line_gap()).ceil() as u32;
+
+    let mut icons: Vec<RgbaImage> = Vec::new();
+    let mut loaded: HashMap<PathBuf, usize> = HashMap::new();
+    let mut items: Vec<Item> = Vec::new();
+    let mut y = options.padding;
+    let mut width = 0;
+
+    let text_lines = std::iter::once(&strip.title)
+        .filter(|title| !title.is_empty())
+        .chain(strip.metadata.iter());
+    for text in text_lines {
+        width = width.max(options.padding * 2 + text_width(&font, text, options.font_size));
+        items.push(Item::Text(text.to_owned(), options.padding, y));
+        y += line_height;
+    }
+    if !items.is_empty() {
+        y += options.spacing;
+    }
+
+    let right_edge = options.max_width.saturating_sub(options.padding);
+    for line in strip.lines.iter() {
+        let mut x = options.padding;
+        let mut row_height = 0;
+        for token in line.iter() {
+            let (path, scale) = match icon(token) {
+                Some(found) => found,
+                None => continue,
+            };
+            let index = match loaded.get(&path) {
+                Some(index) => *index,
+                None => {
+                    icons.push(load_icon(&path, scale)?);
+                    loaded.insert(path, icons.len() - 1);
+                    icons.len() - 1
+                }
+            };
+            let (w, h) = icons[index].dimensions();
+            if x > options.padding && x + w > right_edge {
+                x = options.padding;
+                y += row_height + options.spacing;
+                row_height = 0;
+            }
+            items.push(Item::Icon(index, x, y));
+            x += w + options.spacing;
+            width = width.max(x - options.spacing + options.padding);
+            row_height = row_height.max(h);
+        }
+        y += row_height + options.spacing;
+        items.push(Item::Separator(y));
+        y += 1 + options.spacing;
+    }
+    let height = y + options.padding;
+    let width = width.max(options.padding * 2 + 1);
+
+    let mut image = RgbaImage::from_pixel(width, height, Rgba(options.background));
+    for item in items {
+        match item {
+            Item::Icon(index, x, y) => {
+                image::imageops::overlay(&mut image, &icons[index], x as i64, y as i64)
+            }
+            Item::Text(text, x, y) => draw_text(&mut image, &font, &text, x, y, options),
+            Item::Separator(y) => {
+                for x in options.padding..width - options.padding {
+                    image.put_pixel(x, y, Rgba(options.separator_color));
+                }
+            }
+        }
+    }
+    Ok(image)
+}
+
+pub fn export_png<F>(
+    path: &Path,
+    strip: &ComboStrip,
+    icon: F,
+    options: &ExportOptions,
+) -> Result<(), String>
+where
+    F: Fn(&str) -> Option<(PathBuf, f32)>,
+{
+    let image = render_combo(strip, icon, options)?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    image
+        .save_with_format(path, image::ImageFormat::Png)
+        .map_err(|e| format!("unable to write {}: {}", path.display(), e))
+}
+
+// something safe to use in a file name, "Bnb Beginner" -> "bnb_beginner"
+pub fn file_stem(name: &str) -> String {
+    let stem: String = name
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if stem.is_empty() {
+        "combo".to_owned()
+    } else {
+        stem
+    }
+}
+
+fn load_icon(path: &Path, scale: f32) -> Result<RgbaImage, String> {
+    let icon = image::open(path)
+        .map_err(|e| format!("unable to read {}: {}", path.display(), e))?
+        .to_rgba8();
+    if (scale - 1.0).abs() < f32::EPSILON {
+        return Ok(icon);
+    }
+    let w = ((icon.width() as f32 * scale).round() as u32).max(1);
+    let h = ((icon.height() as f32 * scale).round() as u32).max(1);
+    Ok(image::imageops::resize(&icon, w, h, FilterType::Triangle))
+}
+
+// the same proportional font the window uses
+fn default_font() -> Result<FontVec, String> {
+    let fonts = egui::FontDefinitions::default();
+    let name = fonts.families[&egui::FontFamily::Proportional]
+        .first()
+        .ok_or("no default font")?;
+    let data = &fonts.font_data[name];
+    FontVec::try_from_vec_and_index(data.font.to_vec(), data.index).map_err(|e| e.to_string())
+}
+
+fn text_width(font: &FontVec, text: &str, size: f32) -> u32 {
+    let scaled = font.as_scaled(PxScale::from(size));
+    text.chars()
+        .map(|c| scaled.h_advance(scaled.glyph_id(c)))
+        .sum::<f32>()
+        .ceil() as u32
+}
+
+fn draw_text(
+    image: &mut RgbaImage,
+    font: &FontVec,
+    text: &str,
+    x: u32,
+    y: u32,
+    options: &ExportOptions,
+) {
+    let scale = PxScale::from(options.font_size);
+    let scaled = font.as_scaled(scale);
+    let mut caret = x as f32;
+    let baseline = y as f32 + scaled.ascent();
+    for c in text.chars() {
+        let id = scaled.glyph_id(c);
+        let glyph = id.with_scale_and_position(scale, point(caret, baseline));
+        caret += scaled.h_advance(id);
+        let outlined = match font.outline_glyph(glyph) {
+            Some(outlined) => outlined,
+            None => continue,
+        };
+        let bounds = outlined.px_bounds();
+        outlined.draw(|gx, gy, coverage| {
+            let px = bounds.min.x as i64 + gx as i64;
+            let py = bounds.min.y as i64 + gy as i64;
+            if px < 0 || py < 0 || px >= image.width() as i64 || py >= image.height() as i64 {
+                return;
+            }
+            let pixel = image.get_pixel_mut(px as u32, py as u32);
+            for i in 0..3 {
+                let under = pixel.0[i] as f32;
+                let over = options.text_color[i] as f32;
+                pixel.0[i] = (under + (over - under) * coverage).round() as u8;
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn skg_icon(token: &str) -> Option<(PathBuf, f32)> {
+        let file = match token {
+            "LP" => "Sg_lp.png",
+            "HK" => "Sg_hk.png",
+            _ => return None,
+        };
+        Some((
+            Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("images/skg")
+                .join(file),
+            1.0,
+        ))
+    }
+
+    fn strip() -> ComboStrip {
+        ComboStrip {
+            title: "BnB".to_owned(),
+            metadata: vec!["Game: skg".to_owned()],
+            lines: vec![vec!["2".to_owned(), "LP".to_owned(), "HK".to_owned()]],
+        }
+    }
+
+    fn drawn(image: &RgbaImage, options: &ExportOptions) -> usize {
+        image.pixels().filter(|p| p.0 != options.background).count()
+    }
+
+    #[test]
+    fn renders_icons_and_text() {
+        let options = ExportOptions::default();
+        let image = render_combo(&strip(), skg_icon, &options).unwrap();
+        let lp = image::open(skg_icon("LP").unwrap().0).unwrap();
+        let hk = image::open(skg_icon("HK").unwrap().0).unwrap();
+        let icons_width = options.padding * 2 + lp.width() + options.spacing + hk.width();
+        assert!(image.width() >= icons_width);
+        assert!(image.width() <= options.max_width.max(icons_width));
+        assert!(image.height() > lp.height().max(hk.height()) + options.padding * 2);
+        assert!(drawn(&image, &options) > 0);
+    }
+
+    #[test]
+    fn wraps_past_max_width() {
+        let options = ExportOptions::default();
+        let wide = render_combo(&strip(), skg_icon, &options).unwrap();
+        let narrow_options = ExportOptions {
+            max_width: 1,
+            ..Default::default()
+        };
+        let narrow = render_combo(&strip(), skg_icon, &narrow_options).unwrap();
+        assert!(narrow.height() > wide.height());
+    }
+
+    #[test]
+    fn renders_text_without_icons() {
+        let options = ExportOptions::default();
+        let image = render_combo(&strip(), |_| None, &options).unwrap();
+        assert!(image.width() > options.padding * 2);
+        assert!(drawn(&image, &options) > 0);
+    }
+
+    #[test]
+    fn missing_icon_is_an_error() {
+        let missing = |_: &str| Some((PathBuf::from("images/nope/missing.png"), 1.0));
+        assert!(render_combo(&strip(), missing, &ExportOptions::default()).is_err());
+    }
+
+    #[test]
+    fn file_stems() {
+        assert_eq!(file_stem("Bnb Beginner"), "bnb_beginner");
+        assert_eq!(file_stem("  "), "combo");
+    }
+}
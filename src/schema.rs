@@ -0,0 +1,224 @@
+// Versions of the game file layout. Files say which one they follow in
+// "schema_version" (none means 0) and are brought up to date by running every
+// migration from their version on, in order.
+//
+//   0: the original layout, combos were sometimes written as a list and state
+//      was loosely spelled or missing
+//   1: combos always an index map, state always one of ComboState's names
+//   2: every combo has an id (see library.rs)
+
+use crate::combo::ComboState;
+use serde_json::{Map, Value};
+
+pub const KEY: &str = "schema_version";
+pub const CURRENT: u64 = 2;
+
+// MIGRATIONS[n] takes a file from version n to n + 1, returning what it changed
+type Migration = fn(&mut Value) -> Vec<String>;
+const MIGRATIONS: [Migration; CURRENT as usize] = [v0_to_v1, v1_to_v2];
+
+pub fn version(json: &Value) -> u64 {
+    json[KEY].as_u64().unwrap_or(0)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    pub from: u64,
+    pub to: u64,
+    pub changes: Vec<String>,
+}
+
+impl Report {
+    pub fn changed(&self) -> bool {
+        self.from != self.to || !self.changes.is_empty()
+    }
+}
+
+// Updates `json` in place, files from a newer version of the app are left
+// alone and are an error.
+pub fn migrate(json: &mut Value) -> Result<Report, String> {
+    let from = version(json);
+    if from > CURRENT {
+        return Err(format!(
+            "schema version {} is newer than this app understands ({})",
+            from, CURRENT
+        ));
+    }
+    if !json.is_object() {
+        return Err("not a json object".to_owned());
+    }
+    let mut changes = Vec::new();
+    for (version, migration) in MIGRATIONS.iter().enumerate().skip(from as usize) {
+        for change in migration(json) {
+            changes.push(format!("v{} -> v{}: {}", version, version + 1, change));
+        }
+    }
+    json[KEY] = CURRENT.into();
+    Ok(Report {
+        from,
+        to: CURRENT,
+        changes,
+    })
+}
+
+// every character's name and json, for migrations to walk
+fn characters(json: &mut Value) -> Vec<(String, &mut Value)> {
+    match json.get_mut("characters").and_then(|c| c.as_object_mut()) {
+        Some(characters) => characters
+            .iter_mut()
+            .map(|(name, character)| (name.to_owned(), character))
+            .collect(),
+        None => Vec::new(),
+    }
+}
+
+fn v0_to_v1(json: &mut Value) -> Vec<String> {
+    let mut changes = Vec::new();
+    for (name, character) in characters(json) {
+        if let Some(list) = character["combos"].as_array() {
+            let map: Map<String, Value> = list
+                .iter()
+                .enumerate()
+                .map(|(i, combo)| (i.to_string(), combo.clone()))
+                .collect();
+            character["combos"] = Value::Object(map);
+            changes.push(format!("{}: combo list turned into an index map", name));
+        }
+        let combos = match character["combos"].as_object_mut() {
+            Some(combos) => combos,
+            None => continue,
+        };
+        for (index, combo) in combos.iter_mut() {
+            let combo = match combo.as_object_mut() {
+                Some(combo) => combo,
+                None => continue,
+            };
+            let written = combo.get("state").and_then(|s| s.as_str()).unwrap_or("");
+            let state = ComboState::parse(written).unwrap_or(ComboState::NotDone);
+            if written != state.name() {
+                changes.push(format!(
+                    "{} #{}: state '{}' -> '{}'",
+                    name,
+                    index,
+                    written,
+                    state.name()
+                ));
+                combo.insert("state".to_owned(), state.name().into());
+            }
+        }
+    }
+    changes
+}
+
+fn v1_to_v2(json: &mut Value) -> Vec<String> {
+    let mut count = 0;
+    for (_, character) in characters(json) {
+        let combos = match character["combos"].as_object_mut() {
+            Some(combos) => combos,
+            None => continue,
+        };
+        for (index, combo) in combos.iter_mut() {
+            let combo = match combo.as_object_mut() {
+                Some(combo) => combo,
+                None => continue,
+            };
+            if combo
+                .get("id")
+                .and_then(|id| id.as_str())
+                .unwrap_or("")
+                .is_empty()
+            {
+                // the same key merges used for id-less combos, so migrated
+                // and unmigrated copies of a library still line up
+                combo.insert("id".to_owned(), format!("#{}", index).into());
+                count += 1;
+            }
+        }
+    }
+    if count == 0 {
+        Vec::new()
+    } else {
+        vec![format!("gave {} combo(s) an id", count)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::combo;
+    use serde_json::json;
+
+    fn v0() -> Value {
+        json!({
+            "attacks": { "LP": "" },
+            "characters": {
+                "Ryu": { "combos": [
+                    { "name": "a", "inputs": "2LK", "state": "not done" },
+                    { "name": "b", "inputs": "5HP" }
+                ] },
+                "Ken": { "combos": {
+                    "0": { "id": "k1", "name": "c", "inputs": "623HP", "state": "Done" }
+                } }
+            }
+        })
+    }
+
+    #[test]
+    fn v0_to_current() {
+        let mut json = v0();
+        let report = migrate(&mut json).unwrap();
+        assert_eq!((report.from, report.to), (0, CURRENT));
+        assert_eq!(
+            report.changes,
+            vec![
+                "v0 -> v1: Ryu: combo list turned into an index map",
+                "v0 -> v1: Ryu #0: state 'not done' -> 'NotDone'",
+                "v0 -> v1: Ryu #1: state '' -> 'NotDone'",
+                "v1 -> v2: gave 2 combo(s) an id",
+            ]
+        );
+        assert_eq!(version(&json), CURRENT);
+        let ryu = &json["characters"]["Ryu"]["combos"];
+        assert_eq!(ryu["1"]["state"], "NotDone");
+        assert_eq!(ryu["0"]["id"], "#0");
+        assert_eq!(ryu["1"]["id"], "#1");
+        // ids already there are kept, the rest of the file is untouched
+        assert_eq!(json["characters"]["Ken"]["combos"]["0"]["id"], "k1");
+        assert_eq!(json["attacks"], json!({ "LP": "" }));
+        // and the result reads as characters
+        let characters = combo::characters_from_json(&json).unwrap();
+        assert_eq!(characters.len(), 2);
+    }
+
+    #[test]
+    fn current_is_left_alone() {
+        let mut json = v0();
+        migrate(&mut json).unwrap();
+        let before = json.clone();
+        let report = migrate(&mut json).unwrap();
+        assert!(!report.changed());
+        assert_eq!(json, before);
+    }
+
+    #[test]
+    fn starts_from_the_files_version() {
+        // a v1 file only needs ids
+        let mut json = json!({ KEY: 1, "characters": { "Ryu": { "combos": {
+            "0": { "name": "a", "inputs": "2LK", "state": "whatever" }
+        } } } });
+        let report = migrate(&mut json).unwrap();
+        assert_eq!(report.changes, vec!["v1 -> v2: gave 1 combo(s) an id"]);
+        assert_eq!(
+            json["characters"]["Ryu"]["combos"]["0"]["state"],
+            "whatever"
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let mut newer = json!({ KEY: CURRENT + 1 });
+        assert!(migrate(&mut newer).is_err());
+        assert_eq!(newer, json!({ KEY: CURRENT + 1 }));
+        assert!(migrate(&mut json!([])).is_err());
+    }
+}
@@ -0,0 +1,141 @@
+// Searching every combo of every game in the data directory at once. The
+// index keeps each combo's parsed moves so queries (see query.rs) can look at
+// the route as well as the text.
+
+use crate::combo::{self, Combo};
+use crate::frame_data;
+use crate::icon_pack::IconPack;
+use crate::profile::Profile;
+use crate::query;
+use crate::recorder::{self, Move};
+use crate::schema;
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    // display name as in game_list.json, and the id files use
+    pub game: String,
+    pub game_id: String,
+    pub character: String,
+    pub index: usize,
+    pub combo: Combo,
+    pub tokens: Vec<String>,
+    pub moves: Vec<Move>,
+    // frame data kind of each move, "" when unknown
+    pub kinds: Vec<String>,
+}
+
+impl Entry {
+    // every move's written text run together, "2LK2MP5HKXX236LP"
+    pub fn compact(&self) -> String {
+        compact(&self.combo.inputs)
+    }
+}
+
+pub fn compact(text: &str) -> String {
+    text.chars()
+        .filter(|c| !c.is_whitespace() && *c != ',')
+        .collect::<String>()
+        .to_ascii_uppercase()
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Index {
+    pub entries: Vec<Entry>,
+}
+
+impl Index {
+    // Reads every game in the list, problems with single games are returned
+    // alongside whatever did load.
+    pub fn build(
+        data_dir: &Path,
+        game_list: &Value,
+        default: &Value,
+        packs: &[IconPack],
+    ) -> (Self, Vec<String>) {
+        let mut index = Index::default();
+        let mut errors = Vec::new();
+        let games = game_list.as_object().into_iter().flatten();
+        for (game, id) in games.filter(|(k, _)| *k != "previous_choice") {
+            let id = match id.as_str() {
+                Some(id) => id,
+                None => continue,
+            };
+            let path = data_dir.join(format!("input_{}.json", id));
+            let json: Value = match fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|text| serde_json::from_str(&text).map_err(|e| e.to_string()))
+                .and_then(|mut json| schema::migrate(&mut json).map(|_| json))
+            {
+                Ok(json) => json,
+                Err(e) => {
+                    errors.push(format!("{}: {}", path.display(), e));
+                    continue;
+                }
+            };
+            let profile = Profile {
+                game: &json,
+                default,
+                packs,
+            };
+            let characters = match combo::characters_from_json(&json) {
+                Ok(characters) => characters,
+                Err(e) => {
+                    errors.push(format!("{}: {}", path.display(), e));
+                    continue;
+                }
+            };
+            let buttons = profile.buttons();
+            for character in characters {
+                let data =
+                    frame_data::character_frame_data(&json, &character.name).unwrap_or_default();
+                for (i, combo) in character.combos.into_iter().enumerate() {
+                    let combo = match combo {
+                        Some(combo) if !combo.deleted => combo,
+                        _ => continue,
+                    };
+                    let lines = profile
+                        .map_inputs(&combo.inputs.replace(',', " "))
+                        .unwrap_or_default();
+                    let moves = recorder::moves(&lines, &buttons);
+                    let tokens = lines
+                        .iter()
+                        .flatten()
+                        .filter(|t| !t.trim().is_empty())
+                        .cloned()
+                        .collect();
+                    let kinds = moves
+                        .iter()
+                        .map(|m| {
+                            frame_data::lookup(&data, m)
+                                .map(|d| d.kind.clone())
+                                .unwrap_or_default()
+                        })
+                        .collect();
+                    index.entries.push(Entry {
+                        game: game.to_owned(),
+                        game_id: id.to_owned(),
+                        character: character.name.clone(),
+                        index: i,
+                        combo,
+                        tokens,
+                        moves,
+                        kinds,
+                    });
+                }
+            }
+        }
+        (index, errors)
+    }
+
+    // an empty query finds nothing rather than everything
+    pub fn search(&self, text: &str) -> Result<Vec<&Entry>, String> {
+        if text.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+        let query = query::parse(text)?;
+        Ok(self.entries.iter().filter(|e| query.matches(e)).collect())
+    }
+}
@@ -0,0 +1,39 @@
+// What the overlay was showing when it closed, so the next launch picks up
+// where it left off. Lives in its own session.json next to the game files,
+// game_list.json only lists games.
+
+use serde::Deserialize;
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+
+pub const SESSION: &str = "session.json";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct Session {
+    // display name as in game_list.json
+    pub game: Option<String>,
+    pub character: Option<String>,
+    pub combo: usize,
+    pub show_images: bool,
+    pub text_mode: bool,
+    pub compact: bool,
+    pub window_pos: Option<[f32; 2]>,
+    pub window_size: Option<[f32; 2]>,
+}
+
+impl Session {
+    // a missing or broken file is just a fresh session
+    pub fn load(path: &Path) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let text = serde_json::to_string_pretty(self).unwrap();
+        fs::write(path, text).map_err(|e| format!("unable to write {}: {}", path.display(), e))
+    }
+}
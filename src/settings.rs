@@ -0,0 +1,92 @@
+// How the overlay window behaves and looks, edited from the SETTINGS window
+// and kept in settings.json next to the game files.
+
+use crate::shortcuts;
+use indexmap::IndexMap;
+use serde::Deserialize;
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+
+pub const SETTINGS: &str = "settings.json";
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum Theme {
+    Dark,
+    Light,
+}
+
+impl Theme {
+    pub const ALL: [Theme; 2] = [Theme::Dark, Theme::Light];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Theme::Dark => "Dark",
+            Theme::Light => "Light",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct Settings {
+    pub always_on_top: bool,
+    // background opacity, 1.0 is solid
+    pub opacity: f32,
+    // used when there is no saved session window
+    pub default_size: [f32; 2],
+    pub default_pos: [f32; 2],
+    // body text size, the other text styles scale with it
+    pub font_size: f32,
+    pub icon_scale: f32,
+    pub theme: Theme,
+    // practice mode: clean runs before a combo is marked Done, and seconds
+    // per input when it advances on its own (0 waits for a key)
+    pub practice_runs: u32,
+    pub practice_interval: f32,
+    // action id -> binding, see shortcuts.rs
+    pub shortcuts: IndexMap<String, String>,
+    // name -> search query, see query.rs
+    pub saved_queries: IndexMap<String, String>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            always_on_top: true,
+            opacity: 1.0,
+            default_size: [340.0, 460.0],
+            default_pos: [140.0, 0.0],
+            font_size: 12.5,
+            icon_scale: 1.0,
+            theme: Theme::Dark,
+            practice_runs: 5,
+            practice_interval: 0.0,
+            shortcuts: shortcuts::defaults(),
+            saved_queries: IndexMap::new(),
+        }
+    }
+}
+
+impl Settings {
+    pub const MIN_OPACITY: f32 = 0.2;
+
+    // a missing or broken file gives the defaults, out of range values are clamped
+    pub fn load(path: &Path) -> Self {
+        let mut settings: Settings = fs::read_to_string(path)
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default();
+        settings.opacity = settings.opacity.clamp(Self::MIN_OPACITY, 1.0);
+        settings.font_size = settings.font_size.clamp(8.0, 32.0);
+        settings.icon_scale = settings.icon_scale.clamp(0.25, 4.0);
+        settings.practice_runs = settings.practice_runs.clamp(1, 50);
+        settings.practice_interval = settings.practice_interval.clamp(0.0, 3.0);
+        settings
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let text = serde_json::to_string_pretty(self).unwrap();
+        fs::write(path, text).map_err(|e| format!("unable to write {}: {}", path.display(), e))
+    }
+}
@@ -0,0 +1,129 @@
+// Share codes for passing a single combo around in chat.
+//
+// `en1:` + base64url( crc32(json) as 4 big endian bytes + deflate(json) )
+//
+// The prefix carries the format version so older builds can tell a newer
+// code apart from a corrupted one.
+
+use crate::combo::Combo;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine as _;
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use serde::Deserialize;
+use serde::Serialize;
+use std::io::{Read, Write};
+
+pub const VERSION: u32 = 1;
+const PREFIX: &str = "en";
+// nothing legit comes close, this just stops a hostile code from blowing up
+const MAX_SIZE: u64 = 64 * 1024;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SharedCombo {
+    // game id as in game_list.json ("skg"), not the display name
+    #[serde(rename = "g")]
+    pub game: String,
+    #[serde(rename = "c")]
+    pub character: String,
+    #[serde(rename = "k")]
+    pub combo: Combo,
+}
+
+pub fn encode(shared: &SharedCombo) -> String {
+    let json = serde_json::to_vec(shared).unwrap();
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::best());
+    encoder.write_all(&json).unwrap();
+    let mut data = crc32fast::hash(&json).to_be_bytes().to_vec();
+    data.extend(encoder.finish().unwrap());
+    format!("{}{}:{}", PREFIX, VERSION, URL_SAFE_NO_PAD.encode(data))
+}
+
+pub fn decode(code: &str) -> Result<SharedCombo, String> {
+    let code: String = code.chars().filter(|c| !c.is_whitespace()).collect();
+    let (version, payload) = code
+        .strip_prefix(PREFIX)
+        .and_then(|rest| rest.split_once(':'))
+        .ok_or("not a share code")?;
+    match version.parse::<u32>() {
+        Ok(VERSION) => {}
+        Ok(v) if v > VERSION => {
+            return Err(format!("share code is from a newer version (v{})", v));
+        }
+        _ => return Err(format!("unknown share code version '{}'", version)),
+    }
+    let data = URL_SAFE_NO_PAD
+        .decode(payload)
+        .map_err(|_| "share code is damaged (bad characters)")?;
+    if data.len() < 4 {
+        return Err("share code is too short".to_owned());
+    }
+    let (checksum, compressed) = data.split_at(4);
+    let mut json = Vec::new();
+    DeflateDecoder::new(compressed)
+        .take(MAX_SIZE)
+        .read_to_end(&mut json)
+        .map_err(|_| "share code is damaged (bad data)")?;
+    if crc32fast::hash(&json).to_be_bytes() != checksum {
+        return Err("share code is damaged (checksum mismatch)".to_owned());
+    }
+    serde_json::from_slice(&json).map_err(|e| format!("share code is damaged ({})", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::combo::ComboState;
+
+    fn shared() -> SharedCombo {
+        let mut combo = Combo::new(
+            "BnB".to_owned(),
+            "2LK 2MP 5HK XX 236LP+LK".to_owned(),
+            ComboState::Testing,
+        );
+        combo.notes = "works midscreen".to_owned();
+        SharedCombo {
+            game: "skg".to_owned(),
+            character: "Filia".to_owned(),
+            combo,
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let shared = shared();
+        let code = encode(&shared);
+        assert!(code.starts_with("en1:"));
+        assert_eq!(decode(&code).unwrap(), shared);
+    }
+
+    #[test]
+    fn whitespace_from_chat_is_ignored() {
+        let shared = shared();
+        let code = encode(&shared);
+        let (head, tail) = code.split_at(code.len() / 2);
+        let wrapped = format!("  {}\n {} ", head, tail);
+        assert_eq!(decode(&wrapped).unwrap(), shared);
+    }
+
+    #[test]
+    fn newer_versions_are_told_apart() {
+        let code = encode(&shared()).replacen("en1:", "en9:", 1);
+        assert!(decode(&code).unwrap_err().contains("newer version"));
+        assert!(decode("enx:abc").unwrap_err().contains("unknown"));
+        assert_eq!(decode("hello").unwrap_err(), "not a share code");
+    }
+
+    #[test]
+    fn damage_is_caught() {
+        let code = encode(&shared());
+        assert!(decode(&format!("{}!", code)).is_err());
+        assert!(decode("en1:AAA").unwrap_err().contains("too short"));
+        // flip a byte of the checksum
+        let mut data = URL_SAFE_NO_PAD.decode(&code[4..]).unwrap();
+        data[0] ^= 0xff;
+        let broken = format!("en1:{}", URL_SAFE_NO_PAD.encode(data));
+        assert!(decode(&broken).unwrap_err().contains("checksum"));
+    }
+}
@@ -0,0 +1,275 @@
+// Keyboard shortcuts, stored in settings.json as action name -> "Ctrl+Right"
+// so they can be edited by hand as well as from the SETTINGS window.
+
+use egui::{Key, KeyboardShortcut, Modifiers};
+use indexmap::IndexMap;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Action {
+    NextCombo,
+    PrevCombo,
+    NextCharacter,
+    ToggleIcons,
+    MarkDone,
+    MarkTesting,
+    OpenAdd,
+    ToggleHud,
+    Help,
+    PracticeStep,
+    PracticeLanded,
+    PracticeMiss,
+}
+
+impl Action {
+    pub const ALL: [Action; 12] = [
+        Action::NextCombo,
+        Action::PrevCombo,
+        Action::NextCharacter,
+        Action::ToggleIcons,
+        Action::MarkDone,
+        Action::MarkTesting,
+        Action::OpenAdd,
+        Action::ToggleHud,
+        Action::Help,
+        Action::PracticeStep,
+        Action::PracticeLanded,
+        Action::PracticeMiss,
+    ];
+
+    // key in settings.json
+    pub fn id(&self) -> &'static str {
+        match self {
+            Action::NextCombo => "next_combo",
+            Action::PrevCombo => "prev_combo",
+            Action::NextCharacter => "next_character",
+            Action::ToggleIcons => "toggle_icons",
+            Action::MarkDone => "mark_done",
+            Action::MarkTesting => "mark_testing",
+            Action::OpenAdd => "open_add",
+            Action::ToggleHud => "toggle_hud",
+            Action::Help => "help",
+            Action::PracticeStep => "practice_step",
+            Action::PracticeLanded => "practice_landed",
+            Action::PracticeMiss => "practice_miss",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Action::NextCombo => "Next combo",
+            Action::PrevCombo => "Previous combo",
+            Action::NextCharacter => "Next character",
+            Action::ToggleIcons => "Toggle icons",
+            Action::MarkDone => "Mark combo Done",
+            Action::MarkTesting => "Mark combo Testing",
+            Action::OpenAdd => "Add a combo",
+            Action::ToggleHud => "HUD / full editor",
+            Action::Help => "Show shortcuts",
+            Action::PracticeStep => "Practice: next input",
+            Action::PracticeLanded => "Practice: landed it",
+            Action::PracticeMiss => "Practice: dropped it",
+        }
+    }
+
+    pub fn default_binding(&self) -> &'static str {
+        match self {
+            Action::NextCombo => "Right",
+            Action::PrevCombo => "Left",
+            Action::NextCharacter => "Down",
+            Action::ToggleIcons => "T",
+            Action::MarkDone => "Ctrl+D",
+            Action::MarkTesting => "Ctrl+T",
+            Action::OpenAdd => "Ctrl+N",
+            Action::ToggleHud => "F10",
+            Action::Help => "F1",
+            Action::PracticeStep => "Space",
+            Action::PracticeLanded => "Enter",
+            Action::PracticeMiss => "Backspace",
+        }
+    }
+}
+
+pub fn defaults() -> IndexMap<String, String> {
+    Action::ALL
+        .iter()
+        .map(|a| (a.id().to_owned(), a.default_binding().to_owned()))
+        .collect()
+}
+
+// the binding for `action`, the default one when it's missing or doesn't parse
+pub fn binding(bindings: &IndexMap<String, String>, action: Action) -> KeyboardShortcut {
+    bindings
+        .get(action.id())
+        .and_then(|b| parse(b))
+        .or_else(|| parse(action.default_binding()))
+        .unwrap()
+}
+
+// keys egui reports that make sense as shortcuts
+const KEYS: [Key; 63] = [
+    Key::ArrowDown,
+    Key::ArrowLeft,
+    Key::ArrowRight,
+    Key::ArrowUp,
+    Key::Escape,
+    Key::Tab,
+    Key::Backspace,
+    Key::Enter,
+    Key::Space,
+    Key::Insert,
+    Key::Delete,
+    Key::Home,
+    Key::End,
+    Key::PageUp,
+    Key::PageDown,
+    Key::Num0,
+    Key::Num1,
+    Key::Num2,
+    Key::Num3,
+    Key::Num4,
+    Key::Num5,
+    Key::Num6,
+    Key::Num7,
+    Key::Num8,
+    Key::Num9,
+    Key::A,
+    Key::B,
+    Key::C,
+    Key::D,
+    Key::E,
+    Key::F,
+    Key::G,
+    Key::H,
+    Key::I,
+    Key::J,
+    Key::K,
+    Key::L,
+    Key::M,
+    Key::N,
+    Key::O,
+    Key::P,
+    Key::Q,
+    Key::R,
+    Key::S,
+    Key::T,
+    Key::U,
+    Key::V,
+    Key::W,
+    Key::X,
+    Key::Y,
+    Key::Z,
+    Key::F1,
+    Key::F2,
+    Key::F3,
+    Key::F4,
+    Key::F5,
+    Key::F6,
+    Key::F7,
+    Key::F8,
+    Key::F9,
+    Key::F10,
+    Key::F11,
+    Key::F12,
+];
+
+// "Ctrl+Shift+Right", "F1", "t" (any case, key names as egui's `Key::name`)
+pub fn parse(text: &str) -> Option<KeyboardShortcut> {
+    let mut modifiers = Modifiers::NONE;
+    let mut key = None;
+    for part in text.split('+').map(|p| p.trim()) {
+        match part.to_ascii_lowercase().as_str() {
+            "ctrl" => modifiers = modifiers.plus(Modifiers::CTRL),
+            "shift" => modifiers = modifiers.plus(Modifiers::SHIFT),
+            "alt" => modifiers = modifiers.plus(Modifiers::ALT),
+            name => {
+                if key.is_some() {
+                    return None;
+                }
+                key = Some(
+                    KEYS.into_iter()
+                        .find(|k| k.name().eq_ignore_ascii_case(name))?,
+                );
+            }
+        }
+    }
+    Some(KeyboardShortcut::new(modifiers, key?))
+}
+
+pub fn format(shortcut: &KeyboardShortcut) -> String {
+    let mut parts = Vec::new();
+    if shortcut.modifiers.ctrl {
+        parts.push("Ctrl");
+    }
+    if shortcut.modifiers.shift {
+        parts.push("Shift");
+    }
+    if shortcut.modifiers.alt {
+        parts.push("Alt");
+    }
+    parts.push(shortcut.key.name());
+    parts.join("+")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_modifiers_and_keys() {
+        let shortcut = parse("ctrl+SHIFT + right").unwrap();
+        assert_eq!(shortcut.key, Key::ArrowRight);
+        assert!(shortcut.modifiers.ctrl && shortcut.modifiers.shift && !shortcut.modifiers.alt);
+        assert_eq!(
+            parse("t").unwrap(),
+            KeyboardShortcut::new(Modifiers::NONE, Key::T)
+        );
+    }
+
+    #[test]
+    fn rejects_what_isnt_one_key() {
+        assert_eq!(parse("Ctrl"), None);
+        assert_eq!(parse("A+B"), None);
+        assert_eq!(parse("Hyper+A"), None);
+        assert_eq!(parse(""), None);
+    }
+
+    #[test]
+    fn format_round_trips() {
+        for text in [
+            "Ctrl+Shift+Right",
+            "Alt+F4",
+            "Space",
+            "T",
+            "Ctrl+Alt+Backspace",
+        ] {
+            let shortcut = parse(text).unwrap();
+            assert_eq!(format(&shortcut), text);
+            assert_eq!(parse(&format(&shortcut)), Some(shortcut));
+        }
+    }
+
+    #[test]
+    fn defaults_parse_and_differ() {
+        let bindings: Vec<KeyboardShortcut> = Action::ALL
+            .iter()
+            .map(|a| parse(a.default_binding()).unwrap())
+            .collect();
+        for (i, a) in bindings.iter().enumerate() {
+            assert!(!bindings[i + 1..].contains(a), "{:?} bound twice", a);
+        }
+    }
+
+    #[test]
+    fn bad_bindings_fall_back_to_the_default() {
+        let mut bindings = defaults();
+        bindings.insert("next_combo".to_owned(), "Nonsense".to_owned());
+        bindings.insert("help".to_owned(), "Ctrl+H".to_owned());
+        assert_eq!(
+            binding(&bindings, Action::NextCombo),
+            parse("Right").unwrap()
+        );
+        assert_eq!(binding(&bindings, Action::Help), parse("Ctrl+H").unwrap());
+        bindings.clear();
+        assert_eq!(binding(&bindings, Action::ToggleHud), parse("F10").unwrap());
+    }
+}
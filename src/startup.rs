@@ -0,0 +1,140 @@
+// Command-line flags for the overlay, so different shortcuts (stream deck
+// buttons, desktop links) can open straight into a given setup.
+
+use std::path::PathBuf;
+
+pub const USAGE: &str = "usage: egui_note [options]
+
+options:
+  --game <name|id>          start on this game (display name or id, e.g. skg)
+  --character <name>        select this character
+  --combo <index|name>      select this combo of the character
+  --data-dir <dir>          where game_list.json and input_<game>.json live
+  --compact                 start in the HUD, only the current combo (F10 toggles)
+  --no-always-on-top        open as a normal window
+  -h, --help                show this message";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StartupArgs {
+    pub game: Option<String>,
+    pub character: Option<String>,
+    pub combo: Option<String>,
+    pub data_dir: Option<PathBuf>,
+    pub compact: bool,
+    pub always_on_top: bool,
+}
+
+impl Default for StartupArgs {
+    fn default() -> Self {
+        Self {
+            game: None,
+            character: None,
+            combo: None,
+            data_dir: None,
+            compact: false,
+            always_on_top: true,
+        }
+    }
+}
+
+// Takes the arguments after the program name, `--flag value` and
+// `--flag=value` both work. `Ok(None)` means help was asked for.
+pub fn parse(args: &[String]) -> Result<Option<StartupArgs>, String> {
+    let mut parsed = StartupArgs::default();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let (flag, inline) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => (flag, Some(value.to_owned())),
+            _ => (arg.as_str(), None),
+        };
+        let mut value = || {
+            inline
+                .clone()
+                .or_else(|| args.next().cloned())
+                .ok_or_else(|| format!("{} needs a value", flag))
+        };
+        match flag {
+            "--game" => parsed.game = Some(value()?),
+            "--character" => parsed.character = Some(value()?),
+            "--combo" => parsed.combo = Some(value()?),
+            "--data-dir" => parsed.data_dir = Some(PathBuf::from(value()?)),
+            "--compact" | "--no-always-on-top" | "-h" | "--help" if inline.is_some() => {
+                return Err(format!("{} doesn't take a value", flag))
+            }
+            "--compact" => parsed.compact = true,
+            "--no-always-on-top" => parsed.always_on_top = false,
+            "-h" | "--help" => return Ok(None),
+            _ => return Err(format!("unknown option '{}'", arg)),
+        }
+    }
+    if parsed.combo.is_some() && parsed.character.is_none() {
+        return Err("--combo needs --character".to_owned());
+    }
+    Ok(Some(parsed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parsed(args: &[&str]) -> Result<Option<StartupArgs>, String> {
+        let args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+        parse(&args)
+    }
+
+    #[test]
+    fn defaults() {
+        assert_eq!(parsed(&[]), Ok(Some(StartupArgs::default())));
+        assert!(StartupArgs::default().always_on_top);
+    }
+
+    #[test]
+    fn values_either_way() {
+        let spaced = parsed(&[
+            "--game",
+            "skg",
+            "--character",
+            "Black dahlia",
+            "--combo",
+            "2",
+        ]);
+        let inline = parsed(&["--game=skg", "--character=Black dahlia", "--combo=2"]);
+        assert_eq!(spaced, inline);
+        let args = spaced.unwrap().unwrap();
+        assert_eq!(args.game.as_deref(), Some("skg"));
+        assert_eq!(args.character.as_deref(), Some("Black dahlia"));
+        assert_eq!(args.combo.as_deref(), Some("2"));
+        // only the first "=" splits
+        let args = parsed(&["--data-dir=/tmp/a=b"]).unwrap().unwrap();
+        assert_eq!(args.data_dir, Some(PathBuf::from("/tmp/a=b")));
+    }
+
+    #[test]
+    fn switches() {
+        let args = parsed(&["--compact", "--no-always-on-top"])
+            .unwrap()
+            .unwrap();
+        assert!(args.compact);
+        assert!(!args.always_on_top);
+        assert_eq!(parsed(&["--game", "skg", "-h"]), Ok(None));
+        assert_eq!(parsed(&["--help"]), Ok(None));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            parsed(&["--combo", "2"]),
+            Err("--combo needs --character".to_owned())
+        );
+        assert_eq!(parsed(&["--game"]), Err("--game needs a value".to_owned()));
+        assert_eq!(
+            parsed(&["--colour", "red"]),
+            Err("unknown option '--colour'".to_owned())
+        );
+        assert_eq!(
+            parsed(&["--compact=yes"]),
+            Err("--compact doesn't take a value".to_owned())
+        );
+        assert_eq!(parsed(&["skg"]), Err("unknown option 'skg'".to_owned()));
+    }
+}
@@ -0,0 +1,152 @@
+// Practice results per combo, kept in stats_<game>.json next to the game's
+// input_<game>.json so the library file itself only holds combos.
+//
+// "characters": { "<name>": { "<combo key>": { "attempts", "landed", ... } } }
+//
+// Combos are keyed by Combo::key, their id, so renaming a combo keeps its
+// stats.
+
+use crate::json_file;
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub fn path(data_dir: &Path, game_id: &str) -> PathBuf {
+    data_dir.join(format!("stats_{}.json", game_id))
+}
+
+// days since 1970-01-01, what the per-day history is keyed by
+pub fn today() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() / 86_400)
+        .unwrap_or(0)
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub struct DayStats {
+    pub attempts: u32,
+    pub landed: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct ComboStats {
+    pub attempts: u32,
+    pub landed: u32,
+    // landed in a row right now, and the best run of those
+    pub streak: u32,
+    pub best_streak: u32,
+    // practice input index -> times the combo was dropped there
+    pub drops: BTreeMap<usize, u32>,
+    pub days: BTreeMap<u64, DayStats>,
+}
+
+impl ComboStats {
+    pub fn record_landed(&mut self, day: u64) {
+        self.attempts += 1;
+        self.landed += 1;
+        self.streak += 1;
+        self.best_streak = self.best_streak.max(self.streak);
+        let day = self.days.entry(day).or_default();
+        day.attempts += 1;
+        day.landed += 1;
+    }
+
+    pub fn record_drop(&mut self, day: u64, input: usize) {
+        self.attempts += 1;
+        self.streak = 0;
+        *self.drops.entry(input).or_default() += 1;
+        self.days.entry(day).or_default().attempts += 1;
+    }
+
+    pub fn success_rate(&self) -> Option<f32> {
+        (self.attempts > 0).then(|| self.landed as f32 / self.attempts as f32)
+    }
+
+    // the input index it's dropped at most, with the count
+    pub fn worst_drop(&self) -> Option<(usize, u32)> {
+        self.drops
+            .iter()
+            .max_by_key(|(_, count)| **count)
+            .map(|(input, count)| (*input, *count))
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct Stats {
+    pub characters: BTreeMap<String, BTreeMap<String, ComboStats>>,
+}
+
+impl Stats {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        json_file::load(path)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        json_file::save(path, self)
+    }
+
+    // `key` from Combo::key
+    pub fn combo(&self, character: &str, key: &str) -> Option<&ComboStats> {
+        self.characters.get(character)?.get(key)
+    }
+
+    pub fn combo_mut(&mut self, character: &str, key: &str) -> &mut ComboStats {
+        self.characters
+            .entry(character.to_owned())
+            .or_default()
+            .entry(key.to_owned())
+            .or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::combo::{Character, Combo, ComboState};
+
+    #[test]
+    fn counts_attempts_and_streaks() {
+        let mut stats = ComboStats::default();
+        stats.record_landed(10);
+        stats.record_landed(10);
+        stats.record_drop(11, 3);
+        stats.record_landed(11);
+        assert_eq!((stats.attempts, stats.landed), (4, 3));
+        assert_eq!((stats.streak, stats.best_streak), (1, 2));
+        assert_eq!(stats.worst_drop(), Some((3, 1)));
+        assert_eq!(
+            stats.days[&11],
+            DayStats {
+                attempts: 2,
+                landed: 1
+            }
+        );
+        assert_eq!(stats.success_rate(), Some(0.75));
+    }
+
+    #[test]
+    fn renames_keep_stats() {
+        let mut filia = Character::new(
+            "Filia".to_owned(),
+            vec![Some(Combo::new(
+                "BnB".to_owned(),
+                "2LK 2MP".to_owned(),
+                ComboState::Testing,
+            ))],
+        );
+        let key = filia.combos[0].as_ref().unwrap().key(0);
+        let mut stats = Stats::default();
+        stats.combo_mut("Filia", &key).record_landed(1);
+        filia.combos[0].as_mut().unwrap().name = "BnB 2".to_owned();
+        let key = filia.combos[0].as_ref().unwrap().key(0);
+        assert_eq!(stats.combo("Filia", &key).unwrap().landed, 1);
+        // same name, different combo
+        let other = Combo::new("BnB".to_owned(), "5HP".to_owned(), ComboState::Testing);
+        assert_eq!(stats.combo("Filia", &other.key(1)), None);
+    }
+}
@@ -0,0 +1,116 @@
+// Notices files changed behind the app's back (a text editor, a git pull) by
+// polling their modification time and size, no platform watcher needed.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Stamp {
+    modified: Option<SystemTime>,
+    len: u64,
+}
+
+// None for a file that doesn't exist (yet)
+fn stamp(path: &Path) -> Option<Stamp> {
+    let meta = fs::metadata(path).ok()?;
+    Some(Stamp {
+        modified: meta.modified().ok(),
+        len: meta.len(),
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Watcher {
+    files: Vec<(PathBuf, Option<Stamp>)>,
+    // seconds between looks at the disk
+    pub interval: f64,
+    last_poll: f64,
+}
+
+impl Watcher {
+    pub fn new(interval: f64) -> Self {
+        Self {
+            interval,
+            ..Default::default()
+        }
+    }
+
+    // starts watching `path` as it is now, a path already watched is re-read
+    pub fn watch(&mut self, path: &Path) {
+        self.seen(path);
+    }
+
+    pub fn unwatch(&mut self, path: &Path) {
+        self.files.retain(|(p, _)| p != path);
+    }
+
+    // Takes the file as it is now as known, for after the app wrote it itself.
+    pub fn seen(&mut self, path: &Path) {
+        let now = stamp(path);
+        match self.files.iter_mut().find(|(p, _)| p == path) {
+            Some((_, known)) => *known = now,
+            None => self.files.push((path.to_owned(), now)),
+        }
+    }
+
+    // Files that changed since they were last seen, at most once per
+    // interval. `time` is seconds from any fixed start.
+    pub fn poll(&mut self, time: f64) -> Vec<PathBuf> {
+        if time - self.last_poll < self.interval {
+            return Vec::new();
+        }
+        self.last_poll = time;
+        let mut changed = Vec::new();
+        for (path, known) in self.files.iter_mut() {
+            let now = stamp(path);
+            if now != *known {
+                *known = now;
+                changed.push(path.clone());
+            }
+        }
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scratch_dir;
+
+    #[test]
+    fn reports_a_change_once() {
+        let dir = scratch_dir("watcher");
+        let path = dir.join("input_test.json");
+        fs::write(&path, "{}").unwrap();
+        let mut watcher = Watcher::new(1.0);
+        watcher.watch(&path);
+        assert!(watcher.poll(1.0).is_empty());
+        // the size changes too, so this doesn't hang on mtime resolution
+        fs::write(&path, "{ \"characters\": {} }").unwrap();
+        // not a second since the last look
+        assert!(watcher.poll(1.5).is_empty());
+        assert_eq!(watcher.poll(2.0), vec![path.clone()]);
+        assert!(watcher.poll(3.0).is_empty());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn own_writes_and_new_files() {
+        let dir = scratch_dir("watcher_seen");
+        let path = dir.join("input_test.json");
+        let mut watcher = Watcher::new(0.0);
+        // watched before it exists, showing up is a change
+        watcher.watch(&path);
+        fs::write(&path, "{}").unwrap();
+        assert_eq!(watcher.poll(1.0), vec![path.clone()]);
+        // written by the app itself
+        fs::write(&path, "{ }").unwrap();
+        watcher.seen(&path);
+        assert!(watcher.poll(2.0).is_empty());
+        watcher.unwatch(&path);
+        fs::remove_file(&path).unwrap();
+        assert!(watcher.poll(3.0).is_empty());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
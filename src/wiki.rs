@@ -0,0 +1,349 @@
+// Combos copied off a community wiki: pasted text or a saved page, one route
+// per line in the wiki's own spelling (`c.LK > s.MP xx 236LP+LK`), named
+// after the heading they sit under.
+//
+// The spelling is turned into the app's notation by a dialect, a table of
+// wiki word -> notation. Common fighting game shorthand is built in, a game
+// file can add to it or override it with a "dialect" object:
+//
+//   "dialect": { "c.": "5", "cr.": "2", "xx": "XX" }
+
+use crate::combo::{Combo, ComboState};
+use crate::notation;
+use crate::profile::Profile;
+use regex::{Captures, Regex, RegexBuilder};
+use serde_json::Value;
+
+pub const DIALECT: &str = "dialect";
+
+const BUILT_IN: [(&str, &str); 15] = [
+    ("cl.", "5"),
+    ("c.", "5"),
+    ("far.", "5"),
+    ("st.", "5"),
+    ("s.", "5"),
+    ("cr.", "2"),
+    ("j.", "J"),
+    ("xx", "XX"),
+    ("qcf", "236"),
+    ("qcb", "214"),
+    ("dp", "623"),
+    ("rdp", "421"),
+    ("hcf", "41236"),
+    ("hcb", "63214"),
+    ("=>", ">"),
+];
+
+#[derive(Debug, Clone)]
+pub struct Dialect {
+    // lowercase wiki word -> notation
+    words: Vec<(String, String)>,
+    re: Option<Regex>,
+}
+
+impl Dialect {
+    // the built in words with the game's on top
+    pub fn for_game(game: &Value) -> Self {
+        let mut words: Vec<(String, String)> = BUILT_IN
+            .iter()
+            .map(|(from, to)| (from.to_string(), to.to_string()))
+            .collect();
+        if let Some(table) = game[DIALECT].as_object() {
+            for (from, to) in table {
+                let from = from.to_ascii_lowercase();
+                let to = to.as_str().unwrap_or("").to_owned();
+                match words.iter_mut().find(|(f, _)| *f == from) {
+                    Some(word) => word.1 = to,
+                    None => words.push((from, to)),
+                }
+            }
+        }
+        words.retain(|(from, _)| !from.is_empty());
+        // longest first so "cr." wins over "c."
+        words.sort_by_key(|(from, _)| std::cmp::Reverse(from.len()));
+        let patterns: Vec<String> = words
+            .iter()
+            .map(|(from, _)| {
+                let mut pattern = regex::escape(from);
+                // "dp" is a word, not the middle of "dash"
+                if from.starts_with(|c: char| c.is_ascii_alphanumeric()) {
+                    pattern.insert_str(0, r"\b");
+                }
+                if from.ends_with(|c: char| c.is_ascii_alphanumeric()) {
+                    pattern.push_str(r"\b");
+                }
+                pattern
+            })
+            .collect();
+        let re = RegexBuilder::new(&patterns.join("|"))
+            .case_insensitive(true)
+            .build()
+            .ok();
+        Self { words, re }
+    }
+
+    // `line` in the app's notation, uppercase with commas as spaces
+    pub fn convert(&self, line: &str) -> String {
+        let converted = match self.re.as_ref() {
+            Some(re) => re
+                .replace_all(line, |caps: &Captures| {
+                    let found = caps[0].to_ascii_lowercase();
+                    self.words
+                        .iter()
+                        .find(|(from, _)| *from == found)
+                        .map(|(_, to)| to.clone())
+                        .unwrap_or_else(|| caps[0].to_owned())
+                })
+                .to_string(),
+            None => line.to_owned(),
+        };
+        // "236+LP" is how wikis join a motion to its button, the app writes "236LP"
+        let joined = Regex::new(r"([0-9])\+([A-Za-z])").unwrap();
+        notation::normalize(&joined.replace_all(&converted, "$1$2"))
+    }
+}
+
+pub fn is_html(text: &str) -> bool {
+    let lower = text.to_ascii_lowercase();
+    lower.contains("<html") || lower.contains("</p>") || lower.contains("</h")
+}
+
+// A saved wiki page as plain lines: headings become "# heading" lines, block
+// ends become line breaks and every other tag is dropped.
+pub fn strip_html(html: &str) -> String {
+    let drop = Regex::new(r"(?is)<(script|style)[^>]*>.*?</(script|style)>").unwrap();
+    let heading = Regex::new(r"(?is)<h[1-6][^>]*>(.*?)</h[1-6]>").unwrap();
+    let breaks = Regex::new(r"(?i)<br\s*/?>|</(p|li|tr|div|dd|dt|pre)>").unwrap();
+    let cells = Regex::new(r"(?i)</t[dh]>").unwrap();
+    let tags = Regex::new(r"(?s)<[^>]*>").unwrap();
+    let text = drop.replace_all(html, "");
+    let text = heading.replace_all(&text, "\n# $1\n");
+    let text = breaks.replace_all(&text, "\n");
+    let text = cells.replace_all(&text, " | ");
+    let text = tags.replace_all(&text, "");
+    text.replace("&gt;", ">")
+        .replace("&lt;", "<")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Found {
+    pub name: String,
+    // the line as the wiki wrote it
+    pub source: String,
+    pub inputs: String,
+    // letters the profile doesn't know, worth a look before adding
+    pub unknown: Vec<String>,
+}
+
+impl Found {
+    pub fn to_combo(&self) -> Combo {
+        let mut combo = Combo::new(self.name.clone(), self.inputs.clone(), ComboState::NotDone);
+        combo.notes = format!("wiki: {}", self.source);
+        combo
+    }
+}
+
+// "== Corner ==", "### Corner", "Corner:"
+fn heading(line: &str) -> Option<String> {
+    let trimmed = line.trim();
+    let marked = trimmed.starts_with('#') || (trimmed.starts_with('=') && trimmed.ends_with('='));
+    if marked {
+        return Some(trimmed.trim_matches(['#', '=', ' ']).to_owned());
+    }
+    trimmed
+        .strip_suffix(':')
+        .filter(|t| !t.is_empty())
+        .map(|t| t.trim().to_owned())
+}
+
+// list markers in front of a route, "- ", "* ", "3. "
+fn strip_marker(line: &str) -> &str {
+    let trimmed = line.trim_start();
+    let marker = trimmed
+        .find(char::is_whitespace)
+        .map(|end| &trimmed[..end])
+        .unwrap_or("");
+    let is_marker = matches!(marker, "-" | "*" | "•")
+        || (marker.len() > 1
+            && marker.ends_with(['.', ')'])
+            && marker[..marker.len() - 1]
+                .chars()
+                .all(|c| c.is_ascii_digit()));
+    if is_marker {
+        trimmed[marker.len()..].trim_start()
+    } else {
+        trimmed
+    }
+}
+
+// Every line that reads as a route, named after the heading above it ("Corner",
+// "Corner 2", ...). Other short lines without numbers count as headings too,
+// wikis often don't mark them; anything else is skipped.
+pub fn parse(profile: &Profile, text: &str) -> Result<Vec<Found>, String> {
+    let text = if is_html(text) {
+        strip_html(text)
+    } else {
+        text.to_owned()
+    };
+    let dialect = Dialect::for_game(profile.game);
+    let buttons = profile.buttons();
+    let mut found: Vec<Found> = Vec::new();
+    let mut current = "".to_owned();
+    let mut under_heading = 0;
+    for line in text.lines() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(title) = heading(line) {
+            current = title;
+            under_heading = 0;
+            continue;
+        }
+        let source = strip_marker(line).trim_matches(['|', ' ']).to_owned();
+        let inputs = dialect.convert(&source);
+        let mut tokens: Vec<String> = Vec::new();
+        for line in profile.map_inputs(&inputs).map_err(|e| e.to_string())? {
+            for word in notation::words(&line) {
+                if !notation::is_separator(&word) {
+                    tokens.extend(word);
+                }
+            }
+        }
+        let unknown: Vec<String> = tokens
+            .iter()
+            .filter(|t| t.chars().all(|c| c.is_ascii_alphabetic()))
+            .filter(|t| !buttons.contains(t) && t.as_str() != "J")
+            .cloned()
+            .collect();
+        let has_button = tokens.iter().any(|t| buttons.contains(t));
+        if !has_button || unknown.len() > 2 {
+            // "Corner BnB" but not "4200 dmg"
+            if source.len() <= 40 && !source.contains(|c: char| c.is_ascii_digit()) {
+                current = source;
+                under_heading = 0;
+            }
+            continue;
+        }
+        under_heading += 1;
+        let base = if current.is_empty() {
+            "Imported".to_owned()
+        } else {
+            current.clone()
+        };
+        let name = if under_heading == 1 {
+            base
+        } else {
+            format!("{} {}", base, under_heading)
+        };
+        found.push(Found {
+            name,
+            source,
+            inputs,
+            unknown,
+        });
+    }
+    if found.is_empty() {
+        return Err("no combos found in the text".to_owned());
+    }
+    Ok(found)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profile::with_test_profile;
+    use serde_json::json;
+
+    fn parsed(text: &str) -> Result<Vec<Found>, String> {
+        with_test_profile(|profile| parse(profile, text))
+    }
+
+    #[test]
+    fn built_in_dialect() {
+        let dialect = Dialect::for_game(&json!({}));
+        assert_eq!(
+            dialect.convert("c.LK > s.MP xx 236LP+LK"),
+            "5LK > 5MP XX 236LP+LK"
+        );
+        assert_eq!(
+            dialect.convert("cr.mk, cl.HP XX qcf+hp"),
+            "2MK 5HP XX 236HP"
+        );
+        assert_eq!(dialect.convert("j.HK => rdp+LP"), "JHK > 421LP");
+        // only whole words
+        assert_eq!(dialect.convert("dpad oddp"), "DPAD ODDP");
+    }
+
+    #[test]
+    fn game_dialect() {
+        let dialect = Dialect::for_game(&json!({ DIALECT: { "C.": "2", "tk.": "J" } }));
+        assert_eq!(dialect.convert("c.LK tk.HP cl.MP"), "2LK JHP 5MP");
+    }
+
+    #[test]
+    fn html() {
+        let page = "<html><h2 id=\"c\">Corner</h2><script>var x = 1;</script>\
+                    <ul><li>c.LK &gt; c.MP</li></ul></html>";
+        assert!(is_html(page));
+        assert!(!is_html("c.LK > c.MP"));
+        let text = strip_html(page);
+        assert!(text.contains("# Corner\n"));
+        assert!(text.contains("c.LK > c.MP\n"));
+        assert!(!text.contains("var x"));
+        let found = parsed(page).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].name, "Corner");
+        assert_eq!(found[0].inputs, "5LK > 5MP");
+    }
+
+    #[test]
+    fn headings_and_markers() {
+        assert_eq!(heading("== Corner =="), Some("Corner".to_owned()));
+        assert_eq!(heading("### Corner"), Some("Corner".to_owned()));
+        assert_eq!(heading("Corner:"), Some("Corner".to_owned()));
+        assert_eq!(heading(":"), None);
+        assert_eq!(heading("c.LK > c.MP"), None);
+        assert_eq!(strip_marker("- c.LK"), "c.LK");
+        assert_eq!(strip_marker("  12) c.LK"), "c.LK");
+        assert_eq!(strip_marker("2. c.LK"), "c.LK");
+        assert_eq!(strip_marker("2LK c.LK"), "2LK c.LK");
+    }
+
+    #[test]
+    fn names_follow_headings() {
+        let text = "c.LK > c.MP\n\
+                    == Corner ==\n\
+                    - c.LK > c.MP xx qcf+LP\n\
+                    * cr.LK xx qcf+HP\n\
+                    Midscreen BnB\n\
+                    1. j.HK, cr.MK xx dp+LP\n\
+                    4200 dmg\n";
+        let found = parsed(text).unwrap();
+        let names: Vec<&str> = found.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec!["Imported", "Corner", "Corner 2", "Midscreen BnB"]
+        );
+        assert_eq!(found[1].inputs, "5LK > 5MP XX 236LP");
+        assert_eq!(found[3].inputs, "JHK 2MK XX 623LP");
+        assert_eq!(found[3].source, "j.HK, cr.MK xx dp+LP");
+        let combo = found[3].to_combo();
+        assert_eq!(combo.name, "Midscreen BnB");
+        assert_eq!(combo.notes, "wiki: j.HK, cr.MK xx dp+LP");
+    }
+
+    #[test]
+    fn unknown_words() {
+        // a couple of unknown letters is a route worth a look
+        let found = parsed("c.LK xx qcf+LP xx SA").unwrap();
+        assert!(!found[0].unknown.is_empty());
+        assert!(found[0].unknown.iter().all(|u| !u.contains("LP")));
+        // more reads as prose
+        assert!(parsed("c.LK xx qcf+LP xx super").is_err());
+        assert!(parsed("nothing to see\nhere").is_err());
+    }
+}